@@ -1,6 +1,8 @@
 /* Linker script for the STM32F103C8T6 */
 MEMORY
 {
-  FLASH : ORIGIN = 0x08000000, LENGTH = 64K
+  /* the last two 1K pages store the alarms and the settings, see
+     src/storage.rs */
+  FLASH : ORIGIN = 0x08000000, LENGTH = 62K
   RAM : ORIGIN = 0x20000000, LENGTH = 20K
 }
@@ -0,0 +1,45 @@
+//! Host benchmark of the epoch conversions, run it with
+//! `cargo bench -p portable --target x86_64-unknown-linux-gnu`
+//! (or the target triple of your host).
+
+use portable::datetime::{Date, DateTime};
+use std::hint::black_box;
+use std::time::Instant;
+
+const NB_ITER: u32 = 10_000_000;
+
+fn bench(name: &str, f: impl Fn(u32) -> u32) {
+    // spread the epochs over the whole range, as 429 is coprime with 2^32
+    let step = 429;
+    let start = Instant::now();
+    let mut acc = 0u32;
+    for i in 0..NB_ITER {
+        acc = acc.wrapping_add(f(black_box(i.wrapping_mul(step))));
+    }
+    black_box(acc);
+    let elapsed = start.elapsed();
+    println!(
+        "{:<20} {:>8.2} ns/iter",
+        name,
+        elapsed.as_nanos() as f64 / f64::from(NB_ITER)
+    );
+}
+
+fn main() {
+    bench("DateTime::new", |epoch| {
+        u32::from(DateTime::new(epoch).day())
+    });
+    bench("from_timestamp", |i| {
+        let timestamp = i64::from(i) * 16 - (1 << 35);
+        u32::from(DateTime::from_timestamp(timestamp).unwrap().day())
+    });
+    let dt = DateTime::new(1_540_052_501);
+    bench("DateTime::to_epoch", |i| {
+        let year = 1970 + (i % 136) as u16;
+        let dt = DateTime {
+            date: Date::new(year, dt.month(), dt.day()).unwrap(),
+            ..dt
+        };
+        dt.to_epoch().unwrap_or(0)
+    });
+}
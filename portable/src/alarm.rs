@@ -1,63 +1,407 @@
-use crate::datetime::{DateTime, DayOfWeek};
+use crate::datetime::{Date, DateTime, DayOfWeek, Duration, Time, TimeZone};
+use crate::holidays::Holidays;
+use crate::locale::{Locale, TimeFormat};
 use bitflags::bitflags;
+use core::convert::TryFrom;
 use core::fmt;
+use heapless::{consts::*, String, Vec};
+
+/// The maximum number of alarms.
+pub type MaxAlarms = U16;
+/// The maximum length of the label of an alarm, in ASCII characters. A
+/// label takes 16 bytes of RAM with its length, 256 for a full manager,
+/// which is why the manager is never sent in a message.
+pub type MaxLabelLen = U12;
+/// Marks the saved alarms, the low byte is the version of the format.
+const MAGIC: u16 = 0xa100;
+const VERSION: u16 = 4;
+/// The half words of an alarm, by version of the format.
+const ALARM_LEN: [usize; 5] = [0, 2, 4, 7, 13];
+
+/// Snoozes last 9 minutes, at most 3 times, unless set otherwise.
+pub const DEFAULT_SNOOZE_MINS: u8 = 9;
+pub const DEFAULT_MAX_SNOOZES: u8 = 3;
+pub const MAX_SNOOZE_MINS: u8 = 30;
+pub const MAX_MAX_SNOOZES: u8 = 7;
+/// An alarm late by more than the grace period, in minutes, is missed
+/// and does not ring.
+pub const DEFAULT_GRACE_MINS: u8 = 15;
+pub const MAX_GRACE_MINS: u8 = 60;
+/// A longer jump of the clock is a setting, not skipped time.
+const MAX_CATCH_UP: Duration = Duration::from_days(1);
+/// The last end of vacation, in days since 1970-01-01, 2149-06-06, kept
+/// in the backup domain.
+pub const MAX_VACATION_DAYS: i64 = core::u16::MAX as i64;
 
 #[derive(Debug, Clone)]
 pub struct AlarmManager {
-    pub alarms: [Alarm; 5],
+    pub alarms: Vec<Alarm, MaxAlarms>,
+    /// The public holidays skipped by the alarms asking it.
+    pub holidays: Holidays,
+    /// The alarm ringing, until it is snoozed or dismissed.
+    pub ringing: Option<usize>,
+    pub snooze: Option<Snooze>,
+    /// In `0..=MAX_GRACE_MINS`.
+    pub grace_mins: u8,
+    /// The last alarm missed, until acknowledged.
+    pub missed: Option<Missed>,
+    /// No alarm rings until the end of this day.
+    pub vacation_until: Option<Date>,
+    /// The alarms skipping their next ring, by bit of ID.
+    skipped: u16,
+    /// The UTC timestamp of the previous call to `must_ring`.
+    last: Option<i64>,
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct Snooze {
+    pub id: usize,
+    /// The uptime, in seconds, at which the alarm rings again, not
+    /// changed by the setting of the clock.
+    pub until: u32,
+    /// The number of times the alarm has been snoozed.
+    pub count: u8,
+}
+
+impl Snooze {
+    /// The seconds left at `uptime`, 0 once the alarm rings again.
+    pub fn remaining(&self, uptime: u32) -> u32 {
+        if self.is_over(uptime) {
+            0
+        } else {
+            self.until.wrapping_sub(uptime)
+        }
+    }
+    fn is_over(&self, uptime: u32) -> bool {
+        // the uptime wraps after 136 years
+        (uptime.wrapping_sub(self.until) as i32) >= 0
+    }
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct Missed {
+    pub id: usize,
+    /// When the alarm should have rung.
+    pub at: DateTime,
 }
 impl Default for AlarmManager {
     fn default() -> Self {
         Self {
-            alarms: [
-                Alarm::default(),
-                Alarm::default(),
-                Alarm::default(),
-                Alarm::default(),
-                Alarm::default(),
-            ],
+            alarms: Vec::new(),
+            holidays: Holidays::default(),
+            ringing: None,
+            snooze: None,
+            grace_mins: DEFAULT_GRACE_MINS,
+            missed: None,
+            vacation_until: None,
+            skipped: 0,
+            last: None,
         }
     }
 }
 impl AlarmManager {
-    pub fn must_ring(&mut self, datetime: &DateTime) -> bool {
-        self.alarms
-            .iter_mut()
-            .map(|am| am.must_ring(datetime) as u8)
-            .sum::<u8>()
-            > 0
-    }
-    pub fn next_ring(&self, dt: &DateTime) -> Option<(DayOfWeek, u8, u8)> {
-        let mut min = None;
-        for cur in self.alarms.iter().filter_map(|a| a.next_ring(dt)) {
-            match min {
-                None => min = Some(cur),
-                Some(min_val) => {
-                    let cmp_value = |(dow, h, m)| {
-                        let mut days = (dow as u8 + 7 - dt.day_of_week as u8) % 7;
-                        let now_h = time(dt.hour, dt.min);
-                        let this_h = time(h, m);
-                        if dow == dt.day_of_week && this_h <= now_h {
-                            days += 7
-                        }
-                        u32::from(days) * 60 * 24 + this_h
-                    };
-                    if cmp_value(cur) < cmp_value(min_val) {
-                        min = Some(cur);
+    /// The IDs of the alarms due since the previous call, at the UTC
+    /// timestamp `utc`, or of the snoozed alarm ringing again at
+    /// `uptime`, a new alarm cancelling the snooze. The first one is the
+    /// ringing alarm. The alarms late by more than the grace period are
+    /// missed instead. The alarms due during the vacation do not ring, a
+    /// one time alarm ringing after it. The skipped ones do not ring, a
+    /// one time alarm being lost.
+    ///
+    /// The local times of the alarms are reached once through `tz`: an
+    /// alarm in the hour repeated at the end of DST rings the first time,
+    /// and an alarm in the hour skipped at its start rings at the change.
+    pub fn must_ring(&mut self, utc: i64, tz: &TimeZone, uptime: u32) -> Vec<usize, MaxAlarms> {
+        let since = self.since(utc);
+        self.last = Some(core::cmp::max(since, utc));
+        let mut ids = Vec::new();
+        // everything rings on whole minutes
+        if since.div_euclid(60) != utc.div_euclid(60) {
+            self.alarms_due(since, utc, tz, &mut ids);
+        }
+        match self.snooze {
+            _ if !ids.is_empty() => self.snooze = None,
+            Some(snooze) if self.ringing != Some(snooze.id) && snooze.is_over(uptime) => {
+                ids.push(snooze.id).unwrap();
+            }
+            _ => {}
+        }
+        if let Some(&id) = ids.first() {
+            self.ringing = Some(id);
+        }
+        ids
+    }
+    /// Adds the IDs of the alarms due in the UTC timestamps `(since, utc]`
+    /// to `ids`, or marks them missed.
+    fn alarms_due(&mut self, since: i64, utc: i64, tz: &TimeZone, ids: &mut Vec<usize, MaxAlarms>) {
+        let grace = Duration::from_mins(i64::from(self.grace_mins));
+        let holidays = self.holidays;
+        for (id, alarm) in self.alarms.iter_mut().enumerate() {
+            let due = match alarm.must_ring(since, utc, tz, holidays) {
+                Some(due) => due,
+                None => continue,
+            };
+            if self
+                .vacation_until
+                .filter(|&until| due.date <= until)
+                .is_some()
+            {
+                // an alarm on a date has no later ring: it is missed
+                if alarm.next_ring(&due, holidays).is_none() {
+                    alarm.is_enable = false;
+                    if self.missed.filter(|missed| missed.at > due).is_none() {
+                        self.missed = Some(Missed { id, at: due });
                     }
                 }
+                continue;
+            }
+            if alarm.is_once() {
+                alarm.is_enable = false;
+            }
+            if self.skipped & 1 << id != 0 {
+                self.skipped &= !(1 << id);
+                continue;
+            }
+            if utc - tz.reached_at(&due) <= grace.as_secs() {
+                ids.push(id).unwrap();
+            } else if self.missed.filter(|missed| missed.at > due).is_none() {
+                self.missed = Some(Missed { id, at: due });
+            }
+        }
+    }
+    /// Snoozes the ringing alarm at `uptime`, or dismisses it when it
+    /// has been snoozed too many times. Nothing if no alarm is ringing.
+    pub fn snooze(&mut self, uptime: u32) {
+        let id = match self.ringing.take() {
+            Some(id) => id,
+            None => return,
+        };
+        let count = self.snooze.map_or(0, |s| s.count);
+        let alarm = &self.alarms[id];
+        self.snooze = if count < alarm.max_snoozes {
+            Some(Snooze {
+                id,
+                until: uptime.wrapping_add(u32::from(alarm.snooze_mins) * 60),
+                count: count + 1,
+            })
+        } else {
+            None
+        };
+    }
+    /// Stops the ringing alarm, without snooze.
+    pub fn dismiss(&mut self) {
+        if self.ringing.take().is_some() {
+            self.snooze = None;
+        }
+    }
+    /// Adds an alarm, returning its ID, or the alarm if there is no
+    /// room left.
+    pub fn add(&mut self, alarm: Alarm) -> Result<usize, Alarm> {
+        self.alarms.push(alarm)?;
+        Ok(self.alarms.len() - 1)
+    }
+    /// Removes an alarm, the IDs of the next ones being shifted.
+    pub fn remove(&mut self, id: usize) -> Alarm {
+        let alarm = self.alarms[id].clone();
+        self.alarms = self
+            .alarms
+            .iter()
+            .enumerate()
+            .filter(|&(i, _)| i != id)
+            .map(|(_, alarm)| alarm.clone())
+            .collect();
+        let shift = |i: usize| match i {
+            i if i < id => Some(i),
+            i if i > id => Some(i - 1),
+            _ => None,
+        };
+        self.ringing = self.ringing.and_then(shift);
+        self.snooze = self.snooze.and_then(|s| {
+            Some(Snooze {
+                id: shift(s.id)?,
+                ..s
+            })
+        });
+        self.missed = self.missed.and_then(|m| {
+            Some(Missed {
+                id: shift(m.id)?,
+                ..m
+            })
+        });
+        let skipped = u32::from(self.skipped);
+        self.skipped = (skipped & ((1 << id) - 1) | skipped >> (id + 1) << id) as u16;
+        alarm
+    }
+    pub fn is_skipped(&self, id: usize) -> bool {
+        self.skipped & 1 << id != 0
+    }
+    /// Skips the next ring of an alarm, or not.
+    pub fn set_skipped(&mut self, id: usize, skipped: bool) {
+        if skipped {
+            self.skipped |= 1 << id;
+        } else {
+            self.skipped &= !(1 << id);
+        }
+    }
+    /// The end of the vacation in progress.
+    pub fn on_vacation(&self, dt: &DateTime) -> Option<Date> {
+        self.vacation_until.filter(|&until| dt.date <= until)
+    }
+    /// The skipped alarms and the end of the vacation in days since
+    /// 1970-01-01, 0 being none, kept in the backup domain: they are
+    /// changed by the alarms ringing, too often for the flash.
+    pub fn backup_as_u16s(&self) -> [u16; 2] {
+        // a vacation before 1970 already over
+        let until = match self.vacation_until.map(|until| until.days()) {
+            Some(days) if days > 0 => u16::try_from(days).unwrap_or(core::u16::MAX),
+            _ => 0,
+        };
+        [self.skipped, until]
+    }
+    /// Restores `backup_as_u16s`, the alarms not in the manager being
+    /// ignored.
+    pub fn set_backup(&mut self, data: [u16; 2]) {
+        let ids = 1u32.checked_shl(self.alarms.len() as u32).unwrap_or(0);
+        self.skipped = data[0] & ids.wrapping_sub(1) as u16;
+        self.vacation_until = match data[1] {
+            0 => None,
+            days => Date::from_days(i64::from(days)),
+        };
+    }
+    /// Serializes the alarms as half words, the unit of the flash.
+    pub fn as_u16s(&self) -> Vec<u16, U210> {
+        let mut v = Vec::new();
+        v.push(MAGIC | VERSION).unwrap();
+        v.push(self.alarms.len() as u16).unwrap();
+        for alarm in &self.alarms {
+            for &data in &[alarm.as_u32(), alarm.recurrence.as_u32()] {
+                v.push(data as u16).unwrap();
+                v.push((data >> 16) as u16).unwrap();
+            }
+            let cycle = match alarm.recurrence {
+                Recurrence::Cycle(cycle) => cycle.as_u16s(),
+                _ => [0; 3],
+            };
+            v.extend_from_slice(&cycle).unwrap();
+            v.extend_from_slice(&label_as_u16s(&alarm.label)).unwrap();
+        }
+        v
+    }
+    /// Returns `None` if `data` does not begin with saved alarms, as an
+    /// erased flash page. The invalid alarms are skipped, the alarms of
+    /// the first version being weekly, and only the first `MaxAlarms` are
+    /// loaded.
+    pub fn alarms_try_from(data: &[u16]) -> Option<Vec<Alarm, MaxAlarms>> {
+        let (&magic, data) = data.split_first()?;
+        if magic & 0xff00 != MAGIC || magic & 0xff == 0 || magic & 0xff > VERSION {
+            return None;
+        }
+        let alarm_len = ALARM_LEN[usize::from(magic & 0xff)];
+        let (&len, data) = data.split_first()?;
+        let data = data.get(..usize::from(len) * alarm_len)?;
+        let u32_at = |d: &[u16], i: usize| u32::from(d[i]) | u32::from(d[i + 1]) << 16;
+        let mut alarms = Vec::new();
+        for d in data.chunks(alarm_len) {
+            let mut alarm = match Alarm::try_from(u32_at(d, 0)) {
+                Some(alarm) => alarm,
+                None => continue,
+            };
+            if alarm_len >= 4 {
+                match Recurrence::try_from(u32_at(d, 2)) {
+                    Some(recurrence) => alarm.recurrence = recurrence,
+                    None => continue,
+                }
+            }
+            // the anchor of a cycle is saved as a date
+            match (alarm.recurrence, d.get(4..7)) {
+                (_, Some(&[_, _, 0])) | (_, None) => {}
+                (Recurrence::Date(anchor), Some(cycle)) => match Cycle::try_from(anchor, cycle) {
+                    Some(cycle) => alarm.recurrence = Recurrence::Cycle(cycle),
+                    None => continue,
+                },
+                _ => continue,
+            }
+            if let Some(label) = d.get(7..13) {
+                match label_try_from(label) {
+                    Some(label) => alarm.label = label,
+                    None => continue,
+                }
             }
+            if alarms.push(alarm).is_err() {
+                break;
+            }
+        }
+        Some(alarms)
+    }
+    /// The beginning, excluded, of the time checked by `must_ring`: the
+    /// previous call, or the last minute after a reset or a setting of
+    /// the clock. Set back by less than a minute, as corrected by a time
+    /// source, the alarms already rung do not ring again.
+    fn since(&self, utc: i64) -> i64 {
+        let restart = utc - 60;
+        match self.last {
+            Some(last) if last - utc > 60 => restart,
+            Some(last) if utc - last > MAX_CATCH_UP.as_secs() => restart,
+            Some(last) => last,
+            None => restart,
         }
-        min
+    }
+    /// The snooze in progress at `uptime`, not yet ringing again.
+    pub fn snoozed(&self, uptime: u32) -> Option<Snooze> {
+        self.snooze.filter(|s| !s.is_over(uptime))
+    }
+    pub fn next_ring(&self, dt: &DateTime) -> Option<DateTime> {
+        self.next_alarm(dt).map(|(_, ring)| ring)
+    }
+    /// The ID and the time of the next alarm ringing, the snooze aside.
+    pub fn next_alarm(&self, dt: &DateTime) -> Option<(usize, DateTime)> {
+        (0..self.alarms.len())
+            .filter_map(|id| Some((id, self.rings(id, dt).1?)))
+            .min_by_key(|&(_, ring)| ring)
+    }
+    /// The next ring that will be skipped.
+    pub fn next_skipped(&self, dt: &DateTime) -> Option<DateTime> {
+        (0..self.alarms.len())
+            .filter_map(|id| self.rings(id, dt).0)
+            .min()
+    }
+    /// The ring of an alarm skipped, if any, and its next ring, after
+    /// the vacation.
+    fn rings(&self, id: usize, dt: &DateTime) -> (Option<DateTime>, Option<DateTime>) {
+        let alarm = &self.alarms[id];
+        let from = match self.on_vacation(dt) {
+            Some(until) => DateTime {
+                date: until,
+                time: Time::new(23, 59, 0).unwrap(),
+            },
+            None => *dt,
+        };
+        let first = alarm.next_ring(&from, self.holidays);
+        if !self.is_skipped(id) {
+            return (None, first);
+        }
+        let next = first
+            .filter(|_| !alarm.is_once())
+            .and_then(|first| alarm.next_ring(&first, self.holidays));
+        (first, next)
     }
 }
 
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct Alarm {
     pub is_enable: bool,
-    hour: u8,
-    min: u8,
+    /// As `Work`, empty by default.
+    pub label: String<MaxLabelLen>,
+    time: Time,
+    /// The days of the week of a weekly alarm.
     pub mode: Mode,
+    pub recurrence: Recurrence,
+    /// Does not ring on public holidays, except for one time alarms.
+    pub skip_holidays: bool,
+    /// In `1..=MAX_SNOOZE_MINS`.
+    pub snooze_mins: u8,
+    /// In `0..=MAX_MAX_SNOOZES`.
+    pub max_snoozes: u8,
 }
 
 bitflags! {
@@ -92,103 +436,418 @@ impl Mode {
     }
 }
 
+/// When an alarm rings, besides its time.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Recurrence {
+    /// On the days of `Alarm::mode`, or once.
+    Weekly,
+    /// Once, on a date.
+    Date(Date),
+    /// Every month on a day, the last day of the shorter months.
+    Monthly(u8),
+    /// Every year on a month and a day, the 29th of February being the
+    /// 28th in the common years.
+    Yearly(u8, u8),
+    /// Following a rotation of days, as shifts.
+    Cycle(Cycle),
+}
+impl Default for Recurrence {
+    fn default() -> Self {
+        Recurrence::Weekly
+    }
+}
+impl Recurrence {
+    /// The kind in the bits 0 and 1, the day in the bits 2 to 6, the
+    /// month in the bits 7 to 10 and the year above, 0 being weekly. A
+    /// cycle is its anchor date, the rest being `Cycle::as_u16s`.
+    pub fn as_u32(self) -> u32 {
+        use self::Recurrence::*;
+        let (kind, year, month, day) = match self {
+            Weekly => (0, 0, 0, 0),
+            Date(date) | Cycle(self::Cycle { anchor: date, .. }) => {
+                (1, date.year(), date.month(), date.day())
+            }
+            Monthly(day) => (2, 0, 0, day),
+            Yearly(month, day) => (3, 0, month, day),
+        };
+        kind | u32::from(day) << 2 | u32::from(month) << 7 | u32::from(year) << 11
+    }
+    pub fn try_from(u: u32) -> Option<Self> {
+        let (year, month, day) = (
+            (u >> 11) as u16,
+            (u >> 7) as u8 & 0xf,
+            (u >> 2) as u8 & 0x1f,
+        );
+        match u & 0b11 {
+            0 => Some(Recurrence::Weekly),
+            1 => Date::new(year, month, day).ok().map(Recurrence::Date),
+            2 if (1..=31).contains(&day) => Some(Recurrence::Monthly(day)),
+            // a leap year to accept the 29th of February
+            3 if Date::new(2000, month, day).is_ok() => Some(Recurrence::Yearly(month, day)),
+            _ => None,
+        }
+    }
+}
+
+/// The maximum number of days of a cycle.
+pub const MAX_CYCLE_DAYS: u8 = 16;
+
+/// A rotation of days, as `4 on, 4 off` or `2 early, 2 late, 3 off`,
+/// repeated from an anchor date.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct Cycle {
+    /// The first day of a rotation.
+    pub anchor: Date,
+    /// In `1..=MAX_CYCLE_DAYS`.
+    nb_days: u8,
+    /// The phase of each day, on 2 bits, the days after `nb_days` being
+    /// off.
+    phases: u32,
+    /// The time of the `Phase::Second` days.
+    pub second_time: Time,
+}
+impl Cycle {
+    /// `4 on, 4 off` from `anchor`.
+    pub fn new(anchor: Date, second_time: Time) -> Self {
+        let mut cycle = Self {
+            anchor,
+            nb_days: 8,
+            phases: 0,
+            second_time,
+        };
+        for day in 0..4 {
+            cycle.set_phase(day, Phase::First);
+        }
+        cycle
+    }
+    pub fn nb_days(&self) -> u8 {
+        self.nb_days
+    }
+    /// The days removed are forgotten, the days added are off.
+    pub fn set_nb_days(&mut self, nb_days: u8) {
+        assert!((1..=MAX_CYCLE_DAYS).contains(&nb_days), "invalid cycle");
+        self.nb_days = nb_days;
+        self.phases &= 1u32
+            .checked_shl(2 * u32::from(nb_days))
+            .unwrap_or(0)
+            .wrapping_sub(1);
+    }
+    pub fn phase(&self, day: u8) -> Phase {
+        match (self.phases >> (2 * day)) & 0b11 {
+            1 => Phase::First,
+            2 => Phase::Second,
+            _ => Phase::Off,
+        }
+    }
+    pub fn set_phase(&mut self, day: u8, phase: Phase) {
+        assert!(day < self.nb_days, "invalid day");
+        let bits = match phase {
+            Phase::Off => 0,
+            Phase::First => 1,
+            Phase::Second => 2,
+        };
+        self.phases = self.phases & !(0b11 << (2 * day)) | bits << (2 * day);
+    }
+    /// The phase of `date`, the rotation going on before the anchor.
+    pub fn phase_on(&self, date: Date) -> Phase {
+        let day = (date.days() - self.anchor.days()).rem_euclid(i64::from(self.nb_days));
+        self.phase(day as u8)
+    }
+    /// The phases, then the number of days in the bits 0 to 4 and the
+    /// second time, in minutes, above.
+    pub fn as_u16s(&self) -> [u16; 3] {
+        let second_time = time(self.second_time.hour(), self.second_time.minute());
+        [
+            self.phases as u16,
+            (self.phases >> 16) as u16,
+            u16::from(self.nb_days) | (second_time as u16) << 5,
+        ]
+    }
+    pub fn try_from(anchor: Date, data: &[u16]) -> Option<Self> {
+        let (phases, nb_days, second_time) = match *data {
+            [lo, hi, last] => (
+                u32::from(lo) | u32::from(hi) << 16,
+                (last & 0x1f) as u8,
+                last >> 5,
+            ),
+            _ => return None,
+        };
+        if !(1..=MAX_CYCLE_DAYS).contains(&nb_days) {
+            return None;
+        }
+        let second_time = Time::new((second_time / 60) as u8, (second_time % 60) as u8, 0).ok()?;
+        let cycle = Self {
+            anchor,
+            nb_days,
+            phases,
+            second_time,
+        };
+        let mut valid = cycle;
+        valid.set_nb_days(nb_days);
+        for day in 0..nb_days {
+            valid.set_phase(day, cycle.phase(day));
+        }
+        Some(cycle).filter(|&cycle| cycle == valid)
+    }
+}
+
+/// A day of a cycle.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Phase {
+    Off,
+    /// Rings at the time of the alarm.
+    First,
+    /// Rings at the second time of the cycle.
+    Second,
+}
+impl Phase {
+    pub fn next(self) -> Self {
+        use self::Phase::*;
+        match self {
+            Off => First,
+            First => Second,
+            Second => Off,
+        }
+    }
+    pub fn prev(self) -> Self {
+        use self::Phase::*;
+        match self {
+            Off => Second,
+            First => Off,
+            Second => First,
+        }
+    }
+}
+
 impl Default for Alarm {
     fn default() -> Self {
         Self {
             is_enable: false,
-            hour: 12,
-            min: 0,
+            label: String::new(),
+            time: Time::new(12, 0, 0).unwrap(),
             mode: Mode::default(),
+            recurrence: Recurrence::default(),
+            skip_holidays: false,
+            snooze_mins: DEFAULT_SNOOZE_MINS,
+            max_snoozes: DEFAULT_MAX_SNOOZES,
         }
     }
 }
 impl Alarm {
+    pub fn time(&self) -> Time {
+        self.time
+    }
+    pub fn set_time(&mut self, time: Time) {
+        self.time = time;
+    }
     pub fn hour(&self) -> u8 {
-        self.hour
+        self.time.hour()
     }
     pub fn set_hour(&mut self, h: u8) {
-        assert!(h < 24);
-        self.hour = h;
+        self.time = Time::new(h, self.time.minute(), 0).expect("invalid hour");
     }
     pub fn min(&self) -> u8 {
-        self.min
+        self.time.minute()
     }
     pub fn set_min(&mut self, m: u8) {
-        assert!(m < 60);
-        self.min = m;
+        self.time = Time::new(self.time.hour(), m, 0).expect("invalid minute");
+    }
+    /// The local time at which the alarm is due, reached through `tz` in
+    /// the UTC timestamps `(since, utc]`.
+    pub fn must_ring(
+        &mut self,
+        since: i64,
+        utc: i64,
+        tz: &TimeZone,
+        holidays: Holidays,
+    ) -> Option<DateTime> {
+        let mut from = tz.to_local(since)?;
+        let due = loop {
+            let due = self.next_ring(&from, holidays)?;
+            // the local times repeated at the end of DST already reached
+            match tz.reached_at(&due) {
+                reached if reached > utc => return None,
+                reached if reached > since => break due,
+                _ => from = due,
+            }
+        };
+        Some(due)
     }
-    pub fn must_ring(&mut self, datetime: &DateTime) -> bool {
+    /// The next ring strictly after the minute of `datetime`.
+    pub fn next_ring(&self, datetime: &DateTime, holidays: Holidays) -> Option<DateTime> {
         if !self.is_enable {
-            return false;
-        }
-        if datetime.sec != 0 || datetime.hour != self.hour || datetime.min != self.min {
-            return false;
+            return None;
         }
-        if self.mode.contains(Mode::ONE_TIME) {
-            self.is_enable = false;
-            true
-        } else {
-            self.mode.contains_dow(datetime.day_of_week)
+        let after = DateTime {
+            date: datetime.date,
+            time: Time::new(datetime.hour(), datetime.minute(), 0).unwrap(),
+        };
+        let mut from = datetime.date;
+        loop {
+            let date = self.next_date(from)?;
+            // a year is enough to get past any run of holidays
+            if date.days() - datetime.date.days() > 2 * 366 {
+                return None;
+            }
+            let ring = DateTime {
+                date,
+                time: self.time_on(date),
+            };
+            if ring > after && !self.skips(&date, holidays) {
+                return Some(ring);
+            }
+            from = Date::from_days(date.days() + 1)?;
         }
     }
-    pub fn next_ring(&self, datetime: &DateTime) -> Option<(DayOfWeek, u8, u8)> {
-        if !self.is_enable || self.mode.is_empty() {
-            return None;
+    /// The time of the ring on `date`, depending on the phase of a
+    /// cycle.
+    pub fn time_on(&self, date: Date) -> Time {
+        match self.recurrence {
+            Recurrence::Cycle(cycle) if cycle.phase_on(date) == Phase::Second => cycle.second_time,
+            _ => self.time,
         }
-        let mut day = if time(self.hour, self.min) <= time(datetime.hour, datetime.min) {
-            datetime.day_of_week.next()
-        } else {
-            datetime.day_of_week
+    }
+    /// The first day from `from` on which the alarm rings, the public
+    /// holidays aside.
+    fn next_date(&self, from: Date) -> Option<Date> {
+        let clamped = |year: u16, month: u8, day: u8| {
+            let nb_days = Date::new(year, month, 1).ok()?.days_in_month();
+            Date::new(year, month, core::cmp::min(day, nb_days)).ok()
         };
-        if self.mode.contains(Mode::ONE_TIME) {
-            return Some((day, self.hour, self.min));
-        }
-        loop {
-            if self.mode.contains_dow(day) {
-                return Some((day, self.hour, self.min));
+        match self.recurrence {
+            Recurrence::Weekly if self.mode.contains(Mode::ONE_TIME) => Some(from),
+            Recurrence::Weekly => (0..7)
+                .filter_map(|i| Date::from_days(from.days() + i))
+                .find(|date| self.mode.contains_dow(date.day_of_week())),
+            Recurrence::Date(date) => Some(date).filter(|&date| date >= from),
+            Recurrence::Monthly(day) => {
+                let (year, month) = (from.year(), from.month());
+                Some(clamped(year, month, day)?)
+                    .filter(|&date| date >= from)
+                    .or_else(|| match month {
+                        12 => clamped(year.checked_add(1)?, 1, day),
+                        _ => clamped(year, month + 1, day),
+                    })
             }
-            day = day.next();
+            Recurrence::Yearly(month, day) => Some(clamped(from.year(), month, day)?)
+                .filter(|&date| date >= from)
+                .or_else(|| clamped(from.year().checked_add(1)?, month, day)),
+            Recurrence::Cycle(cycle) => (0..i64::from(cycle.nb_days()))
+                .filter_map(|i| Date::from_days(from.days() + i))
+                .find(|&date| cycle.phase_on(date) != Phase::Off),
+        }
+    }
+    /// Rings once, and is then disabled.
+    pub fn is_once(&self) -> bool {
+        match self.recurrence {
+            Recurrence::Weekly => self.mode.contains(Mode::ONE_TIME),
+            Recurrence::Date(_) => true,
+            _ => false,
         }
     }
+    fn skips(&self, date: &Date, holidays: Holidays) -> bool {
+        self.skip_holidays && !self.is_once() && holidays.contains(date)
+    }
+    /// The snooze settings are in the bits left free by the hour and
+    /// above the bit 26, stored so that the alarms saved before get the
+    /// default ones.
     pub fn as_u32(&self) -> u32 {
         self.mode.bits() as u32
-            | (self.min as u32) << 8
-            | (self.hour as u32) << 16
+            | (self.min() as u32) << 8
+            | (self.hour() as u32) << 16
+            | ((self.max_snoozes ^ DEFAULT_MAX_SNOOZES) as u32) << 21
             | (self.is_enable as u32) << 24
             | 1 << 25
+            | (self.skip_holidays as u32) << 26
+            | (self.snooze_mins as u32) << 27
     }
     pub fn try_from(u: u32) -> Option<Self> {
-        if u >> 25 != 1 {
+        if (u >> 25) & 1 != 1 {
             return None;
         }
-        let res = Self {
+        let snooze_mins = match (u >> 27) as u8 {
+            0 => DEFAULT_SNOOZE_MINS,
+            mins if mins <= MAX_SNOOZE_MINS => mins,
+            _ => return None,
+        };
+        Some(Self {
             is_enable: (u & 1 << 24) != 0,
-            hour: (u >> 16) as u8,
-            min: (u >> 8) as u8,
+            label: String::new(),
+            time: Time::new((u >> 16) as u8 & 0x1f, (u >> 8) as u8, 0).ok()?,
             mode: Mode::from_bits_truncate(u as u8),
-        };
-        if res.hour > 23 || res.min > 59 {
-            return None;
+            recurrence: Recurrence::Weekly,
+            skip_holidays: (u & 1 << 26) != 0,
+            snooze_mins,
+            max_snoozes: (u >> 21) as u8 & 0b111 ^ DEFAULT_MAX_SNOOZES,
+        })
+    }
+}
+impl Alarm {
+    /// The summary of the alarm, as `On  07:25 Mo Tu`, in `locale`.
+    pub fn localized(&self, locale: Locale, time_format: TimeFormat) -> Localized<'_> {
+        Localized {
+            alarm: self,
+            locale,
+            time_format,
         }
-        Some(res)
     }
 }
 impl fmt::Display for Alarm {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        if self.is_enable {
-            f.write_str("On  ")?;
-        } else {
-            f.write_str("Off ")?;
-        }
-        write!(f, "{:02}:{:02}", self.hour, self.min)?;
-        if self.mode.contains(Mode::ONE_TIME) {
-            f.write_str(" one time")?;
-        } else if self.mode.is_empty() {
-            f.write_str(" never")?;
+        self.localized(Locale::English, TimeFormat::TwentyFourHour)
+            .fmt(f)
+    }
+}
+
+pub struct Localized<'a> {
+    alarm: &'a Alarm,
+    locale: Locale,
+    time_format: TimeFormat,
+}
+impl<'a> fmt::Display for Localized<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let (alarm, strings) = (self.alarm, self.locale.strings());
+        let state = if alarm.is_enable {
+            strings.on
         } else {
-            for &(dow, s) in VEC_DAY_OF_WEEK_SHORT_NAME.iter() {
-                if self.mode.contains(dow) {
-                    f.write_str(s)?;
+            strings.off
+        };
+        let (hour, suffix) = self.time_format.hour(alarm.hour());
+        write!(f, "{:4}{:02}:{:02}{}", state, hour, alarm.min(), suffix)?;
+        match alarm.recurrence {
+            Recurrence::Date(date) => write!(
+                f,
+                " {:04}-{:02}-{:02}",
+                date.year(),
+                date.month(),
+                date.day()
+            )?,
+            Recurrence::Monthly(day) => write!(f, " {} {}", strings.monthly, day)?,
+            Recurrence::Cycle(cycle) => {
+                write!(f, " {} ", strings.cycle)?;
+                for day in 0..cycle.nb_days() {
+                    let c = match cycle.phase(day) {
+                        Phase::Off => '-',
+                        Phase::First => '1',
+                        Phase::Second => '2',
+                    };
+                    write!(f, "{}", c)?;
+                }
+            }
+            Recurrence::Yearly(month, day) => {
+                write!(f, " {} {:02}-{:02}", strings.yearly, month, day)?
+            }
+            Recurrence::Weekly if alarm.mode.contains(Mode::ONE_TIME) => {
+                write!(f, " {}", strings.one_time)?
+            }
+            Recurrence::Weekly if alarm.mode.is_empty() => write!(f, " {}", strings.never)?,
+            Recurrence::Weekly => {
+                let mut dow = DayOfWeek::Monday;
+                for _ in 0..7 {
+                    if alarm.mode.contains_dow(dow) {
+                        write!(f, " {}", self.locale.day_of_week_short(dow))?;
+                    }
+                    dow = dow.next();
                 }
             }
         }
@@ -196,15 +855,29 @@ impl fmt::Display for Alarm {
     }
 }
 
-static VEC_DAY_OF_WEEK_SHORT_NAME: [(Mode, &str); 7] = [
-    (Mode::MONDAY, " Mo"),
-    (Mode::TUESDAY, " Tu"),
-    (Mode::WEDNESDAY, " We"),
-    (Mode::THURSDAY, " Th"),
-    (Mode::FRIDAY, " Fr"),
-    (Mode::SATURDAY, " Sa"),
-    (Mode::SUNDAY, " Su"),
-];
+/// Two characters by half word, the first in the low byte, padded with
+/// 0.
+fn label_as_u16s(label: &str) -> [u16; 6] {
+    let mut data = [0; 6];
+    for (i, &b) in label.as_bytes().iter().enumerate() {
+        data[i / 2] |= u16::from(b) << (i % 2 * 8);
+    }
+    data
+}
+
+/// Only printable ASCII characters are valid.
+fn label_try_from(data: &[u16]) -> Option<String<MaxLabelLen>> {
+    let mut label = String::new();
+    let mut bytes = (0..data.len() * 2).map(|i| (data[i / 2] >> (i % 2 * 8)) as u8);
+    for b in bytes.by_ref().take_while(|&b| b != 0) {
+        match b {
+            0x20..=0x7e => label.push(char::from(b)).ok()?,
+            _ => return None,
+        }
+    }
+    Some(label).filter(|_| bytes.all(|b| b == 0))
+}
+
 fn time(hour: u8, min: u8) -> u32 {
     u32::from(hour) * 60 + u32::from(min)
 }
@@ -212,9 +885,29 @@ fn time(hour: u8, min: u8) -> u32 {
 #[cfg(test)]
 mod test {
     use super::*;
+    use crate::datetime::Date;
+
+    fn dt(year: u16, month: u8, day: u8, hour: u8, min: u8) -> DateTime {
+        DateTime {
+            date: Date::new(year, month, day).unwrap(),
+            time: Time::new(hour, min, 0).unwrap(),
+        }
+    }
+
+    /// `must_ring` with the local time in UTC.
+    fn must_ring(
+        alarm_manager: &mut AlarmManager,
+        dt: &DateTime,
+        uptime: u32,
+    ) -> Vec<usize, MaxAlarms> {
+        alarm_manager.must_ring(dt.timestamp(), &TimeZone::utc(), uptime)
+    }
 
     fn default_alarm_manager() -> AlarmManager {
         let mut alarm_manager = AlarmManager::default();
+        for _ in 0..5 {
+            alarm_manager.add(Alarm::default()).unwrap();
+        }
         alarm_manager.alarms[0].is_enable = true;
         alarm_manager.alarms[0].set_hour(7);
         alarm_manager.alarms[0].set_min(25);
@@ -231,17 +924,12 @@ mod test {
         let alarm_manager = default_alarm_manager();
 
         let datetime = DateTime {
-            year: 2018,
-            month: 10,
-            day: 17,
-            hour: 9,
-            min: 0,
-            sec: 0,
-            day_of_week: DayOfWeek::Wednesday,
+            date: Date::new(2018, 10, 17).unwrap(),
+            time: Time::new(9, 0, 0).unwrap(),
         };
 
         let next = alarm_manager.next_ring(&datetime);
-        assert_eq!(next, Some((DayOfWeek::Thursday, 7, 25)));
+        assert_eq!(next, Some(dt(2018, 10, 18, 7, 25)));
     }
 
     #[test]
@@ -253,17 +941,12 @@ mod test {
         alarm_manager.alarms[2].mode.insert(Mode::ONE_TIME);
 
         let datetime = DateTime {
-            year: 2018,
-            month: 12,
-            day: 1,
-            hour: 17,
-            min: 21,
-            sec: 0,
-            day_of_week: DayOfWeek::Saturday,
+            date: Date::new(2018, 12, 1).unwrap(),
+            time: Time::new(17, 21, 0).unwrap(),
         };
 
         let next = alarm_manager.next_ring(&datetime);
-        assert_eq!(next, Some((DayOfWeek::Saturday, 17, 30)));
+        assert_eq!(next, Some(dt(2018, 12, 1, 17, 30)));
     }
 
     #[test]
@@ -275,17 +958,12 @@ mod test {
         alarm_manager.alarms[2].mode = Mode::empty();
 
         let datetime = DateTime {
-            year: 2018,
-            month: 12,
-            day: 1,
-            hour: 17,
-            min: 21,
-            sec: 0,
-            day_of_week: DayOfWeek::Saturday,
+            date: Date::new(2018, 12, 1).unwrap(),
+            time: Time::new(17, 21, 0).unwrap(),
         };
 
         let next = alarm_manager.next_ring(&datetime);
-        assert_eq!(next, Some((DayOfWeek::Monday, 7, 25)));
+        assert_eq!(next, Some(dt(2018, 12, 3, 7, 25)));
     }
 
     #[test]
@@ -294,10 +972,566 @@ mod test {
         let mut alarm = Alarm::default();
         assert_eq!(Some(alarm.clone()), Alarm::try_from(alarm.as_u32()));
         assert_eq!(None, Alarm::try_from(alarm.as_u32() | 60 << 8));
-        assert_eq!(None, Alarm::try_from(alarm.as_u32() | 1 << 26));
+        assert_eq!(None, Alarm::try_from(alarm.as_u32() | 31 << 27));
         alarm.set_hour(0);
         assert_eq!(None, Alarm::try_from(alarm.as_u32() | 24 << 16));
         alarm.is_enable = true;
         assert_eq!(Some(alarm.clone()), Alarm::try_from(alarm.as_u32()));
+        alarm.skip_holidays = true;
+        assert_eq!(Some(alarm.clone()), Alarm::try_from(alarm.as_u32()));
+        alarm.snooze_mins = MAX_SNOOZE_MINS;
+        alarm.max_snoozes = 0;
+        assert_eq!(Some(alarm.clone()), Alarm::try_from(alarm.as_u32()));
+        alarm.max_snoozes = MAX_MAX_SNOOZES;
+        assert_eq!(Some(alarm.clone()), Alarm::try_from(alarm.as_u32()));
+        // saved without the snooze settings
+        let old = Alarm::try_from(1 << 25 | 7 << 16).unwrap();
+        assert_eq!(old.snooze_mins, DEFAULT_SNOOZE_MINS);
+        assert_eq!(old.max_snoozes, DEFAULT_MAX_SNOOZES);
+    }
+
+    #[test]
+    fn test_recurrence() {
+        let mut alarm = Alarm {
+            is_enable: true,
+            ..Alarm::default()
+        };
+        alarm.set_hour(8);
+        let at = |date: Date| DateTime {
+            date,
+            time: Time::new(8, 0, 0).unwrap(),
+        };
+        let now = dt(2026, 1, 31, 9, 0);
+        let date = Date::new(2026, 12, 24).unwrap();
+        alarm.recurrence = Recurrence::Date(date);
+        assert_eq!(alarm.next_ring(&now, Holidays::None), Some(at(date)));
+        assert_eq!(alarm.next_ring(&at(date), Holidays::None), None);
+        assert!(alarm.is_once());
+
+        // the last day of the shorter months
+        alarm.recurrence = Recurrence::Monthly(31);
+        let next = alarm.next_ring(&now, Holidays::None);
+        assert_eq!(next, Some(dt(2026, 2, 28, 8, 0)));
+        let next = alarm.next_ring(&dt(2026, 12, 31, 8, 0), Holidays::None);
+        assert_eq!(next, Some(dt(2027, 1, 31, 8, 0)));
+        alarm.recurrence = Recurrence::Monthly(1);
+        let next = alarm.next_ring(&dt(2026, 1, 1, 7, 0), Holidays::None);
+        assert_eq!(next, Some(dt(2026, 1, 1, 8, 0)));
+        // on New Year's Day
+        alarm.skip_holidays = true;
+        let next = alarm.next_ring(&dt(2026, 1, 1, 7, 0), Holidays::France);
+        assert_eq!(next, Some(dt(2026, 2, 1, 8, 0)));
+        alarm.skip_holidays = false;
+
+        alarm.recurrence = Recurrence::Yearly(2, 29);
+        let next = alarm.next_ring(&now, Holidays::None);
+        assert_eq!(next, Some(dt(2026, 2, 28, 8, 0)));
+        let next = alarm.next_ring(&dt(2027, 3, 1, 8, 0), Holidays::None);
+        assert_eq!(next, Some(dt(2028, 2, 29, 8, 0)));
+        assert!(!alarm.is_once());
+
+        let mut alarm_manager = AlarmManager::default();
+        alarm.recurrence = Recurrence::Date(date);
+        alarm_manager.add(alarm).unwrap();
+        assert_eq!(alarm_manager.next_ring(&now), Some(at(date)));
+        assert!(must_ring(&mut alarm_manager, &at(date).add_days(-1).unwrap(), 0).is_empty());
+        assert_eq!(&must_ring(&mut alarm_manager, &at(date), 0)[..], [0]);
+        assert!(!alarm_manager.alarms[0].is_enable);
+
+        for &recurrence in &[
+            Recurrence::Weekly,
+            Recurrence::Date(date),
+            Recurrence::Monthly(31),
+            Recurrence::Yearly(2, 29),
+        ] {
+            assert_eq!(Recurrence::try_from(recurrence.as_u32()), Some(recurrence));
+        }
+        assert_eq!(Recurrence::try_from(2), None);
+        assert_eq!(Recurrence::try_from(3 | 30 << 2 | 2 << 7), None);
+    }
+
+    #[test]
+    fn test_cycle() {
+        use std::string::ToString;
+        let mut alarm = Alarm {
+            is_enable: true,
+            ..Alarm::default()
+        };
+        alarm.set_hour(6);
+        // 2 early, 2 late, 3 off, from a Monday
+        let mut cycle = Cycle::new(
+            Date::new(2026, 10, 5).unwrap(),
+            Time::new(14, 0, 0).unwrap(),
+        );
+        cycle.set_nb_days(7);
+        for (day, &phase) in [Phase::First, Phase::First, Phase::Second, Phase::Second]
+            .iter()
+            .enumerate()
+        {
+            cycle.set_phase(day as u8, phase);
+        }
+        alarm.recurrence = Recurrence::Cycle(cycle);
+        assert_eq!(alarm.to_string(), "On  06:00 Cycle 1122---");
+        let next = |dt: DateTime| alarm.next_ring(&dt, Holidays::None);
+        assert_eq!(next(dt(2026, 10, 5, 5, 0)), Some(dt(2026, 10, 5, 6, 0)));
+        assert_eq!(next(dt(2026, 10, 6, 6, 0)), Some(dt(2026, 10, 7, 14, 0)));
+        assert_eq!(next(dt(2026, 10, 8, 14, 0)), Some(dt(2026, 10, 12, 6, 0)));
+        // before the anchor
+        assert_eq!(next(dt(2026, 10, 1, 12, 0)), Some(dt(2026, 10, 1, 14, 0)));
+        assert_eq!(next(dt(2026, 10, 2, 12, 0)), Some(dt(2026, 10, 5, 6, 0)));
+        assert!(!alarm.is_once());
+
+        // 4 on, 4 off
+        let mut alarm_manager = AlarmManager::default();
+        alarm.recurrence = Recurrence::Cycle(Cycle::new(
+            Date::new(2026, 10, 5).unwrap(),
+            Time::new(14, 0, 0).unwrap(),
+        ));
+        alarm_manager.add(alarm).unwrap();
+        assert_eq!(
+            &must_ring(&mut alarm_manager, &dt(2026, 10, 8, 6, 0), 0)[..],
+            [0]
+        );
+        assert!(must_ring(&mut alarm_manager, &dt(2026, 10, 9, 6, 0), 0).is_empty());
+        assert_eq!(
+            &must_ring(&mut alarm_manager, &dt(2026, 10, 13, 6, 0), 0)[..],
+            [0]
+        );
+
+        let mut cycle = Cycle::new(
+            Date::new(2026, 10, 5).unwrap(),
+            Time::new(14, 0, 0).unwrap(),
+        );
+        cycle.set_nb_days(3);
+        assert_eq!(cycle.phase(3), Phase::Off);
+        cycle.set_nb_days(8);
+        assert_eq!(cycle.phase(3), Phase::Off);
+        let data = cycle.as_u16s();
+        assert_eq!(Cycle::try_from(cycle.anchor, &data), Some(cycle));
+        assert_eq!(Cycle::try_from(cycle.anchor, &[0, 0, 0]), None);
+        assert_eq!(Cycle::try_from(cycle.anchor, &[0b11, 0, data[2]]), None);
+        assert_eq!(Cycle::try_from(cycle.anchor, &[0, 1, data[2]]), None);
+    }
+
+    #[test]
+    fn test_skip_and_vacation() {
+        let mut alarm_manager = default_alarm_manager();
+        alarm_manager.alarms[1].is_enable = false;
+        // Thursday
+        let ring = dt(2020, 5, 21, 7, 25);
+        let at = |days: i64| ring.add_days(days).unwrap();
+        let before = at(-1);
+        alarm_manager.set_skipped(0, true);
+        assert!(alarm_manager.is_skipped(0));
+        assert_eq!(alarm_manager.next_skipped(&before), Some(ring));
+        assert_eq!(alarm_manager.next_ring(&before), Some(at(1)));
+        assert!(must_ring(&mut alarm_manager, &ring, 0).is_empty());
+        assert!(!alarm_manager.is_skipped(0));
+        assert_eq!(alarm_manager.missed, None);
+        assert_eq!(&must_ring(&mut alarm_manager, &at(1), 0)[..], [0]);
+        assert_eq!(alarm_manager.next_skipped(&at(1)), None);
+
+        // until Monday included
+        alarm_manager.vacation_until = Some(at(4).date);
+        alarm_manager.set_skipped(0, true);
+        assert_eq!(alarm_manager.on_vacation(&at(4)), Some(at(4).date));
+        assert_eq!(alarm_manager.on_vacation(&at(5)), None);
+        assert!(must_ring(&mut alarm_manager, &at(4), 0).is_empty());
+        assert!(alarm_manager.is_skipped(0));
+        assert_eq!(alarm_manager.next_skipped(&at(4)), Some(at(5)));
+        assert_eq!(alarm_manager.next_ring(&at(4)), Some(at(7)));
+        let backup = alarm_manager.backup_as_u16s();
+        let mut restored = default_alarm_manager();
+        restored.set_backup(backup);
+        assert_eq!(restored.vacation_until, alarm_manager.vacation_until);
+        assert!(restored.is_skipped(0));
+        restored.set_backup([0xffff, 0]);
+        assert_eq!(restored.vacation_until, None);
+        assert_eq!(restored.backup_as_u16s(), [0b1_1111, 0]);
+        // the skipped IDs follow the removed alarms
+        restored.remove(1);
+        assert_eq!(restored.backup_as_u16s(), [0b1111, 0]);
+        restored.set_skipped(1, false);
+        restored.remove(0);
+        assert_eq!(restored.backup_as_u16s(), [0b110, 0]);
+
+        // kept until 2149-06-06 only
+        alarm_manager.vacation_until = Date::from_days(MAX_VACATION_DAYS + 1);
+        assert_eq!(alarm_manager.backup_as_u16s()[1], 0xffff);
+        alarm_manager.vacation_until = Date::new(1969, 7, 20).ok();
+        assert_eq!(alarm_manager.backup_as_u16s()[1], 0);
+
+        // a skipped one time alarm
+        alarm_manager.vacation_until = None;
+        alarm_manager.alarms[0].mode.insert(Mode::ONE_TIME);
+        assert_eq!(alarm_manager.next_skipped(&before), Some(ring));
+        assert_eq!(alarm_manager.next_ring(&before), None);
+        assert!(must_ring(&mut alarm_manager, &ring, 0).is_empty());
+        assert!(!alarm_manager.alarms[0].is_enable);
+
+        // a one time alarm due during the vacation rings after it
+        let mut alarm_manager = default_alarm_manager();
+        alarm_manager.alarms[1].is_enable = false;
+        alarm_manager.alarms[0].mode = Mode::ONE_TIME;
+        alarm_manager.vacation_until = Some(at(1).date);
+        assert!(must_ring(&mut alarm_manager, &ring, 0).is_empty());
+        assert!(must_ring(&mut alarm_manager, &at(1), 0).is_empty());
+        assert!(alarm_manager.alarms[0].is_enable);
+        assert_eq!(alarm_manager.next_ring(&at(1)), Some(at(2)));
+        assert_eq!(&must_ring(&mut alarm_manager, &at(2), 0)[..], [0]);
+        assert!(!alarm_manager.alarms[0].is_enable);
+
+        // an alarm on a date due during the vacation is missed
+        alarm_manager.alarms[0].is_enable = true;
+        alarm_manager.alarms[0].recurrence = Recurrence::Date(ring.date);
+        assert!(must_ring(&mut alarm_manager, &ring, 0).is_empty());
+        assert!(!alarm_manager.alarms[0].is_enable);
+        assert_eq!(alarm_manager.missed, Some(Missed { id: 0, at: ring }));
+    }
+
+    #[test]
+    fn test_add_remove() {
+        let mut alarm_manager = default_alarm_manager();
+        for id in 5..16 {
+            assert_eq!(alarm_manager.add(Alarm::default()), Ok(id));
+        }
+        assert_eq!(alarm_manager.add(Alarm::default()), Err(Alarm::default()));
+
+        let ring = DateTime {
+            date: Date::new(2020, 5, 21).unwrap(),
+            time: Time::new(7, 25, 0).unwrap(),
+        };
+        alarm_manager.alarms[4] = alarm_manager.alarms[0].clone();
+        alarm_manager.alarms[0].is_enable = false;
+        assert_eq!(&must_ring(&mut alarm_manager, &ring, 0)[..], [4]);
+        alarm_manager.snooze(0);
+        alarm_manager.missed = Some(Missed { id: 3, at: ring });
+        assert_eq!(alarm_manager.remove(2), Alarm::default());
+        assert_eq!(alarm_manager.alarms.len(), 15);
+        assert_eq!(alarm_manager.snooze.unwrap().id, 3);
+        assert_eq!(alarm_manager.missed.unwrap().id, 2);
+        alarm_manager.remove(3);
+        assert_eq!(alarm_manager.snooze, None);
+        assert_eq!(alarm_manager.missed.unwrap().id, 2);
+    }
+
+    #[test]
+    fn test_as_u16s() {
+        let mut alarm_manager = default_alarm_manager();
+        alarm_manager.alarms[2].skip_holidays = true;
+        alarm_manager.alarms[3].recurrence = Recurrence::Date(Date::new(2026, 12, 24).unwrap());
+        alarm_manager.alarms[4].recurrence = Recurrence::Yearly(2, 29);
+        let mut cycle = Cycle::new(
+            Date::new(2026, 10, 5).unwrap(),
+            Time::new(14, 5, 0).unwrap(),
+        );
+        cycle.set_nb_days(MAX_CYCLE_DAYS);
+        cycle.set_phase(MAX_CYCLE_DAYS - 1, Phase::Second);
+        alarm_manager.alarms[1].recurrence = Recurrence::Cycle(cycle);
+        alarm_manager.alarms[4].label = String::from("Kids school!");
+        alarm_manager.alarms[2].label = String::from("Pills");
+        let data = alarm_manager.as_u16s();
+        assert_eq!(data.len(), 67);
+        assert_eq!(
+            AlarmManager::alarms_try_from(&data),
+            Some(alarm_manager.alarms.clone())
+        );
+        // an invalid alarm is skipped
+        let mut data = data.clone();
+        data[3] = 0;
+        let alarms = AlarmManager::alarms_try_from(&data).unwrap();
+        assert_eq!(&alarms[..], &alarm_manager.alarms[1..]);
+        assert_eq!(
+            AlarmManager::alarms_try_from(&[MAGIC | VERSION, 0]),
+            Some(Vec::new())
+        );
+        assert_eq!(
+            AlarmManager::alarms_try_from(&[MAGIC | VERSION, 2, 1]),
+            None
+        );
+        assert_eq!(AlarmManager::alarms_try_from(&[0xffff; 40]), None);
+        // more alarms than room for them
+        let alarm_len = ALARM_LEN[usize::from(VERSION)];
+        let mut data = Vec::<u16, U256>::new();
+        data.extend_from_slice(&[MAGIC | VERSION, 17]).unwrap();
+        for id in 0..17 {
+            let alarm = &alarm_manager.as_u16s()[2..][id % 5 * alarm_len..][..alarm_len];
+            data.extend_from_slice(alarm).unwrap();
+        }
+        let alarms = AlarmManager::alarms_try_from(&data).unwrap();
+        assert_eq!(alarms.len(), 16);
+        assert_eq!(alarms[15], alarm_manager.alarms[0]);
+        // the first version, weekly alarms only
+        let alarm = &alarm_manager.alarms[0];
+        let v1 = [
+            MAGIC | 1,
+            1,
+            alarm.as_u32() as u16,
+            (alarm.as_u32() >> 16) as u16,
+        ];
+        let alarms = AlarmManager::alarms_try_from(&v1).unwrap();
+        assert_eq!(&alarms[..], &alarm_manager.alarms[..1]);
+        // the second version, without the cycles
+        let alarm = &alarm_manager.alarms[3];
+        let mut v2 = std::vec![MAGIC | 2, 1];
+        for &data in &[alarm.as_u32(), alarm.recurrence.as_u32()] {
+            v2.extend_from_slice(&[data as u16, (data >> 16) as u16]);
+        }
+        let alarms = AlarmManager::alarms_try_from(&v2).unwrap();
+        assert_eq!(&alarms[..], &alarm_manager.alarms[3..4]);
+        // an invalid cycle
+        let mut data = alarm_manager.as_u16s();
+        data[2 + 13 + 6] = 0xffff;
+        let alarms = AlarmManager::alarms_try_from(&data).unwrap();
+        assert_eq!(alarms.len(), 4);
+        assert_eq!(alarms[1], alarm_manager.alarms[2]);
+        // an invalid label, after the end or not printable
+        for &(i, half_word) in &[(2 + 2 * 13 + 7 + 5, 0x41), (2 + 2 * 13 + 7, 0x1f)] {
+            let mut data = alarm_manager.as_u16s();
+            data[i] = half_word;
+            let alarms = AlarmManager::alarms_try_from(&data).unwrap();
+            assert_eq!(&alarms[2..], &alarm_manager.alarms[3..]);
+        }
+        // the third version, without the labels
+        let mut v3 = std::vec![MAGIC | 3, 1];
+        v3.extend_from_slice(&alarm_manager.as_u16s()[2 + 13..2 + 13 + 7]);
+        let alarms = AlarmManager::alarms_try_from(&v3).unwrap();
+        assert_eq!(&alarms[..], &alarm_manager.alarms[1..2]);
+        assert_eq!(AlarmManager::alarms_try_from(&[]), None);
+    }
+
+    #[test]
+    fn test_must_ring() {
+        let mut alarm_manager = default_alarm_manager();
+        alarm_manager.alarms[3] = alarm_manager.alarms[0].clone();
+        // Thursday
+        let datetime = DateTime {
+            date: Date::new(2020, 5, 21).unwrap(),
+            time: Time::new(7, 25, 0).unwrap(),
+        };
+        assert_eq!(&must_ring(&mut alarm_manager, &datetime, 0)[..], [0, 3]);
+        assert_eq!(alarm_manager.ringing, Some(0));
+        let datetime = datetime.add_days(-1).unwrap();
+        assert!(must_ring(&mut alarm_manager, &datetime, 0).is_empty());
+        assert_eq!(alarm_manager.ringing, Some(0));
+        alarm_manager.dismiss();
+        assert_eq!(alarm_manager.ringing, None);
+    }
+
+    #[test]
+    fn test_snooze() {
+        let mut alarm_manager = default_alarm_manager();
+        alarm_manager.alarms[0].max_snoozes = 2;
+        // Thursday
+        let ring = DateTime {
+            date: Date::new(2020, 5, 21).unwrap(),
+            time: Time::new(7, 25, 0).unwrap(),
+        };
+        let at = |mins: i64| ring.add_seconds(mins * 60).unwrap();
+        // the uptime, in seconds
+        let up = |mins: u32| 1000 + mins * 60;
+        assert_eq!(&must_ring(&mut alarm_manager, &ring, up(0))[..], [0]);
+        assert_eq!(alarm_manager.ringing, Some(0));
+        alarm_manager.snooze(up(0) + 20);
+        assert_eq!(alarm_manager.ringing, None);
+        let snooze = alarm_manager.snoozed(up(1)).unwrap();
+        assert_eq!((snooze.until, snooze.count), (up(9) + 20, 1));
+        assert_eq!(snooze.remaining(up(1)), 8 * 60 + 20);
+
+        // the clock set forward by an hour does not change the snooze
+        assert!(must_ring(&mut alarm_manager, &at(61), up(1)).is_empty());
+        assert!(must_ring(&mut alarm_manager, &at(69), up(9)).is_empty());
+        assert_eq!(&must_ring(&mut alarm_manager, &at(69), up(9) + 20)[..], [0]);
+        assert_eq!(alarm_manager.ringing, Some(0));
+        assert_eq!(alarm_manager.snoozed(up(9) + 20), None);
+        // ringing once
+        assert!(must_ring(&mut alarm_manager, &at(69), up(9) + 21).is_empty());
+        alarm_manager.snooze(up(10));
+        assert_eq!(alarm_manager.snoozed(up(10)).unwrap().count, 2);
+        assert_eq!(&must_ring(&mut alarm_manager, &at(79), up(19))[..], [0]);
+        // no snooze left
+        alarm_manager.snooze(up(19));
+        assert_eq!(alarm_manager.ringing, None);
+        assert_eq!(alarm_manager.snooze, None);
+        assert!(must_ring(&mut alarm_manager, &at(88), up(28)).is_empty());
+
+        // dismissed
+        let up = |mins: u32| up(24 * 60 + mins);
+        assert_eq!(&must_ring(&mut alarm_manager, &at(24 * 60), up(0))[..], [0]);
+        alarm_manager.snooze(up(0));
+        alarm_manager.dismiss();
+        assert!(alarm_manager.snoozed(up(0)).is_some());
+        assert_eq!(
+            &must_ring(&mut alarm_manager, &at(24 * 60 + 9), up(9))[..],
+            [0]
+        );
+        alarm_manager.dismiss();
+        assert_eq!(alarm_manager.snooze, None);
+        assert_eq!(
+            alarm_manager.next_ring(&at(24 * 60 + 9)),
+            Some(dt(2020, 5, 25, 7, 25))
+        );
+
+        // the uptime wrapping
+        assert_eq!(
+            &must_ring(&mut alarm_manager, &at(4 * 24 * 60), !0)[..],
+            [0]
+        );
+        alarm_manager.snooze(!0);
+        assert_eq!(
+            alarm_manager
+                .snoozed(9 * 60 - 2)
+                .unwrap()
+                .remaining(9 * 60 - 2),
+            1
+        );
+        assert_eq!(
+            &must_ring(&mut alarm_manager, &at(4 * 24 * 60 + 9), 9 * 60 - 1)[..],
+            [0]
+        );
+    }
+
+    #[test]
+    fn test_catch_up() {
+        let mut alarm_manager = default_alarm_manager();
+        // Thursday
+        let ring = DateTime {
+            date: Date::new(2020, 5, 21).unwrap(),
+            time: Time::new(7, 25, 0).unwrap(),
+        };
+        let at = |days: i64, secs: i64| ring.add_days(days).unwrap().add_seconds(secs).unwrap();
+        // seconds skipped
+        assert!(must_ring(&mut alarm_manager, &at(0, -2), 0).is_empty());
+        assert_eq!(&must_ring(&mut alarm_manager, &at(0, 1), 0)[..], [0]);
+        assert!(must_ring(&mut alarm_manager, &at(0, 2), 0).is_empty());
+        // set back by a time source
+        assert!(must_ring(&mut alarm_manager, &at(0, -1), 0).is_empty());
+        assert!(must_ring(&mut alarm_manager, &at(0, 0), 0).is_empty());
+
+        // set forward, late by 14 minutes
+        assert!(must_ring(&mut alarm_manager, &at(1, -300), 0).is_empty());
+        assert_eq!(&must_ring(&mut alarm_manager, &at(1, 14 * 60), 0)[..], [0]);
+        assert_eq!(alarm_manager.missed, None);
+
+        // late by 20 minutes, on Monday
+        assert!(must_ring(&mut alarm_manager, &at(4, -300), 0).is_empty());
+        assert!(must_ring(&mut alarm_manager, &at(4, 20 * 60), 0).is_empty());
+        let missed = Missed {
+            id: 0,
+            at: at(4, 0),
+        };
+        assert_eq!(alarm_manager.missed, Some(missed));
+        alarm_manager.grace_mins = 30;
+        assert!(must_ring(&mut alarm_manager, &at(5, -300), 0).is_empty());
+        assert_eq!(&must_ring(&mut alarm_manager, &at(5, 20 * 60), 0)[..], [0]);
+        assert_eq!(alarm_manager.missed, Some(missed));
+
+        // reset during the minute
+        let mut alarm_manager = default_alarm_manager();
+        assert_eq!(&must_ring(&mut alarm_manager, &at(0, 40), 0)[..], [0]);
+        // the clock set far away is not a missed alarm
+        assert!(must_ring(&mut alarm_manager, &at(7, -60), 0).is_empty());
+        assert!(must_ring(&mut alarm_manager, &at(-7, 60), 0).is_empty());
+        assert_eq!(alarm_manager.missed, None);
+    }
+
+    #[test]
+    fn test_dst() {
+        let paris = TimeZone::parse("CET-1CEST,M3.5.0,M10.5.0/3").unwrap();
+        let mut alarm_manager = AlarmManager::default();
+        for &(hour, min) in &[(1, 30), (2, 30), (3, 0)] {
+            let mut alarm = Alarm::default();
+            alarm.is_enable = true;
+            alarm.set_hour(hour);
+            alarm.set_min(min);
+            alarm.mode = Mode::SUNDAY;
+            alarm_manager.add(alarm).unwrap();
+        }
+        // the rings, at the local time, over 5 hours every 20 seconds
+        let mut rings_from = |from: i64| {
+            let mut rings: Vec<(usize, DateTime), U8> = Vec::new();
+            for utc in (from..from + 5 * 3600).step_by(20) {
+                for id in alarm_manager.must_ring(utc, &paris, 0) {
+                    rings.push((id, paris.to_local(utc).unwrap())).unwrap();
+                }
+            }
+            rings
+        };
+
+        // 2018-03-25, from 00:00 CET: 02:30 does not exist, ringing at
+        // 03:00 CEST
+        let rings = rings_from(1521932400);
+        let expected = [
+            (0, dt(2018, 3, 25, 1, 30)),
+            (1, dt(2018, 3, 25, 3, 0)),
+            (2, dt(2018, 3, 25, 3, 0)),
+        ];
+        assert_eq!(&rings[..], expected);
+
+        // 2018-10-28, from 00:00 CEST: 02:30 occurs twice, ringing once
+        let rings = rings_from(1540677600);
+        let expected = [
+            (0, dt(2018, 10, 28, 1, 30)),
+            (1, dt(2018, 10, 28, 2, 30)),
+            (2, dt(2018, 10, 28, 3, 0)),
+        ];
+        assert_eq!(&rings[..], expected);
+        assert_eq!(alarm_manager.missed, None);
+    }
+
+    #[test]
+    fn test_skip_holidays() {
+        let mut alarm_manager = default_alarm_manager();
+        alarm_manager.alarms[1].is_enable = false;
+        // Thursday 2020-05-21 is Ascension Day, and Friday is not a
+        // holiday
+        let ascension = DateTime {
+            date: Date::new(2020, 5, 21).unwrap(),
+            time: Time::new(7, 25, 0).unwrap(),
+        };
+        let wednesday = ascension.add_days(-1).unwrap();
+        assert_eq!(
+            &must_ring(&mut alarm_manager.clone(), &ascension, 0)[..],
+            [0]
+        );
+        assert_eq!(alarm_manager.next_ring(&wednesday), Some(ascension));
+
+        alarm_manager.alarms[0].skip_holidays = true;
+        assert!(must_ring(&mut alarm_manager.clone(), &ascension, 0).is_empty());
+        assert_eq!(
+            alarm_manager.next_ring(&wednesday),
+            Some(ascension.add_days(1).unwrap())
+        );
+        alarm_manager.holidays = Holidays::Germany;
+        assert!(must_ring(&mut alarm_manager.clone(), &ascension, 0).is_empty());
+        alarm_manager.holidays = Holidays::None;
+        assert_eq!(
+            &must_ring(&mut alarm_manager.clone(), &ascension, 0)[..],
+            [0]
+        );
+
+        // one time alarms ring anyway
+        alarm_manager.holidays = Holidays::France;
+        alarm_manager.alarms[0].mode.insert(Mode::ONE_TIME);
+        assert_eq!(&must_ring(&mut alarm_manager, &ascension, 0)[..], [0]);
+    }
+
+    #[test]
+    fn test_display() {
+        use std::string::ToString;
+        let mut alarm_manager = default_alarm_manager();
+        let alarm = &alarm_manager.alarms[0];
+        assert_eq!(alarm.to_string(), "On  07:25 Mo Tu Th Fr");
+        let french = alarm.localized(Locale::French, TimeFormat::TwentyFourHour);
+        assert_eq!(french.to_string(), "Oui 07:25 Lu Ma Je Ve");
+        let english = alarm.localized(Locale::English, TimeFormat::TwelveHour);
+        assert_eq!(english.to_string(), "On  07:25 AM Mo Tu Th Fr");
+        let german = alarm_manager.alarms[1].localized(Locale::German, TimeFormat::TwentyFourHour);
+        assert_eq!(german.to_string(), "An  08:15 Mi");
+        alarm_manager.alarms[2].mode = Mode::empty();
+        assert_eq!(alarm_manager.alarms[2].to_string(), "Off 12:00 never");
+        alarm_manager.alarms[2].mode = Mode::ONE_TIME;
+        let spanish = alarm_manager.alarms[2].localized(Locale::Spanish, TimeFormat::TwelveHour);
+        assert_eq!(spanish.to_string(), "No  12:00 PM una vez");
     }
 }
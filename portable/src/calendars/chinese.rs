@@ -0,0 +1,194 @@
+//! The Chinese lunisolar calendar, computed from the new moons and the
+//! solar terms at the meridian of China (UTC+8) as in Calendrical
+//! Calculations by Reingold and Dershowitz.
+//!
+//! The months begin on the day of a new moon, the winter solstice is in
+//! the 11th month, and when there are 13 months between two 11th
+//! months, the first month without a major solar term is a leap month,
+//! numbered as the previous one. A new moon or a solar term a few
+//! minutes from midnight may give a date off by a day.
+
+use crate::datetime::Date;
+use crate::moon;
+use crate::sun::solar_longitude;
+use core::fmt;
+
+const CHINA_OFFSET: i64 = 8 * 3600;
+const SYNODIC_MONTH: f32 = 29.530_588;
+
+static STEMS: [&str; 10] = [
+    "Jia", "Yi", "Bing", "Ding", "Wu", "Ji", "Geng", "Xin", "Ren", "Gui",
+];
+static BRANCHES: [&str; 12] = [
+    "zi", "chou", "yin", "mao", "chen", "si", "wu", "wei", "shen", "you", "xu", "hai",
+];
+static ANIMALS: [&str; 12] = [
+    "Rat", "Ox", "Tiger", "Rabbit", "Dragon", "Snake", "Horse", "Goat", "Monkey", "Rooster", "Dog",
+    "Pig",
+];
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct ChineseDate {
+    /// The Gregorian year in which the Chinese year begins.
+    pub year: i32,
+    /// In `1..=12`.
+    pub month: u8,
+    /// A leap month, following the month of the same number.
+    pub leap: bool,
+    pub day: u8,
+}
+impl ChineseDate {
+    pub fn from_date(date: &Date) -> Self {
+        let day = date.days();
+        let solstice = winter_solstice_on_or_before(day);
+        let next_solstice = winter_solstice_on_or_before(solstice + 370);
+        // the 12th month and the 11th month of the next year
+        let month_12 = new_moon_on_or_after(solstice + 1);
+        let next_month_11 = new_moon_before(next_solstice + 1);
+        let leap_year = nb_months(month_12, next_month_11) == 12;
+
+        let start = new_moon_before(day + 1);
+        let after_leap = leap_year && prior_leap_month(month_12, start);
+        let month = (nb_months(month_12, start) - if after_leap { 1 } else { 0 }) as i32;
+        let month = (month + 11) % 12 + 1;
+        let leap = leap_year
+            && no_major_solar_term(start)
+            && !prior_leap_month(month_12, new_moon_before(start));
+
+        let solstice_year = i32::from(Date::from_days(solstice).map_or(0, Date::year));
+        let before_new_year = month >= 11 && day - solstice < 90;
+        Self {
+            year: solstice_year + if before_new_year { 0 } else { 1 },
+            month: month as u8,
+            leap,
+            day: (day - start + 1) as u8,
+        }
+    }
+    /// The name of the year in the sexagenary cycle, as `Gengzi`.
+    pub fn year_name(self) -> YearName {
+        YearName(self.year)
+    }
+    /// The animal of the zodiac of the year.
+    pub fn animal(self) -> &'static str {
+        ANIMALS[(self.year - 4).rem_euclid(12) as usize]
+    }
+}
+impl fmt::Display for ChineseDate {
+    /// As `Gengzi Rat 8-15`, the month being prefixed by `L` when it is
+    /// a leap month.
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let leap = if self.leap { "L" } else { "" };
+        let (name, animal) = (self.year_name(), self.animal());
+        write!(f, "{} {} {}{}-{}", name, animal, leap, self.month, self.day)
+    }
+}
+
+pub struct YearName(i32);
+impl fmt::Display for YearName {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        // 1984 is the first year of the cycle
+        let i = (self.0 - 4).rem_euclid(60) as usize;
+        write!(f, "{}{}", STEMS[i % 10], BRANCHES[i % 12])
+    }
+}
+
+/// The timestamp of the beginning of `day` in China.
+fn midnight(day: i64) -> i64 {
+    day * 86400 - CHINA_OFFSET
+}
+/// The day in China at the timestamp `t`.
+fn day_of(t: i64) -> i64 {
+    (t + CHINA_OFFSET).div_euclid(86400)
+}
+fn new_moon_on_or_after(day: i64) -> i64 {
+    day_of(moon::new_moon_after(midnight(day) - 1))
+}
+fn new_moon_before(day: i64) -> i64 {
+    day_of(moon::new_moon_before(midnight(day)))
+}
+/// The number of months between two first days of months.
+fn nb_months(from: i64, to: i64) -> i64 {
+    libm::roundf((to - from) as f32 / SYNODIC_MONTH) as i64
+}
+
+/// The last major solar term at the beginning of `day`, the 11th one
+/// being the winter solstice.
+fn major_solar_term(day: i64) -> i64 {
+    (2 + (solar_longitude(midnight(day)) / 30.) as i64) % 12
+}
+fn no_major_solar_term(month_start: i64) -> bool {
+    major_solar_term(month_start) == major_solar_term(new_moon_on_or_after(month_start + 1))
+}
+/// Whether there is a leap month between `from` and `month_start`
+/// included.
+fn prior_leap_month(from: i64, mut month_start: i64) -> bool {
+    while month_start >= from {
+        if no_major_solar_term(month_start) {
+            return true;
+        }
+        month_start = new_moon_before(month_start);
+    }
+    false
+}
+/// The day of the last winter solstice in China, the sun reaching a
+/// longitude of 270° before the end of the day.
+fn winter_solstice_on_or_before(day: i64) -> i64 {
+    let past_solstice = |day: i64| {
+        let degrees = solar_longitude(midnight(day + 1)) - 270.;
+        degrees > 0. && degrees < 90.
+    };
+    // the sun moves by about a degree a day
+    let since = (solar_longitude(midnight(day + 1)) + 90.) % 360.;
+    let mut solstice = day - (since * 365.242 / 360.) as i64 - 3;
+    while !past_solstice(solstice) {
+        solstice += 1;
+    }
+    solstice
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn chinese(year: u16, month: u8, day: u8) -> ChineseDate {
+        ChineseDate::from_date(&Date::new(year, month, day).unwrap())
+    }
+    fn date(year: i32, month: u8, leap: bool, day: u8) -> ChineseDate {
+        ChineseDate {
+            year,
+            month,
+            leap,
+            day,
+        }
+    }
+
+    #[test]
+    fn test_new_year() {
+        assert_eq!(chinese(2020, 1, 25), date(2020, 1, false, 1));
+        assert_eq!(chinese(2020, 1, 24), date(2019, 12, false, 30));
+        assert_eq!(chinese(2021, 2, 12), date(2021, 1, false, 1));
+        assert_eq!(chinese(2023, 1, 22), date(2023, 1, false, 1));
+        assert_eq!(chinese(1985, 2, 20), date(1985, 1, false, 1));
+    }
+
+    #[test]
+    fn test_leap_month() {
+        assert_eq!(chinese(2020, 5, 22), date(2020, 4, false, 30));
+        assert_eq!(chinese(2020, 5, 23), date(2020, 4, true, 1));
+        assert_eq!(chinese(2020, 6, 21), date(2020, 5, false, 1));
+        // Mid-Autumn Festival
+        assert_eq!(chinese(2020, 10, 1), date(2020, 8, false, 15));
+        assert_eq!(chinese(2023, 3, 22), date(2023, 2, true, 1));
+        // a leap month after the winter solstice
+        assert_eq!(chinese(2033, 12, 22), date(2033, 11, true, 1));
+        assert_eq!(chinese(2034, 1, 20), date(2033, 12, false, 1));
+    }
+
+    #[test]
+    fn test_display() {
+        use std::string::ToString;
+        assert_eq!(chinese(2020, 10, 1).to_string(), "Gengzi Rat 8-15");
+        assert_eq!(chinese(2023, 3, 22).to_string(), "Guimao Rabbit L2-1");
+        assert_eq!(chinese(1984, 6, 1).year_name().to_string(), "Jiazi");
+    }
+}
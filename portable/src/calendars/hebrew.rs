@@ -0,0 +1,150 @@
+//! The Hebrew calendar, computed with the molad of Tishrei and the
+//! rules postponing the new year.
+
+use crate::datetime::Date;
+use core::fmt;
+
+/// 1 Tishrei 1 (3761 BC, October 7 Julian) in days since 1970-01-01.
+const EPOCH: i64 = -2_092_590;
+/// Parts of hour in a day, an hour having 1080 parts.
+const PARTS_PER_DAY: i64 = 25_920;
+
+/// The months numbered from Nisan as in the Torah, the year beginning
+/// in Tishrei, the seventh month. Adar is the twelfth month, or Adar I
+/// followed by Adar II in leap years.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct HebrewDate {
+    pub year: u32,
+    /// In `1..=13`.
+    pub month: u8,
+    pub day: u8,
+}
+impl HebrewDate {
+    pub fn from_date(date: &Date) -> Self {
+        let days = date.days();
+        // the mean year being 365.2468 days, this is the year or one of
+        // the two before
+        let mut year = (days - EPOCH) * 10_000 / 3_652_468;
+        while new_year(year + 1) <= days {
+            year += 1;
+        }
+        let nb_months = if is_leap(year) { 13 } else { 12 };
+        let mut month = 7;
+        let mut start = new_year(year);
+        loop {
+            let len = i64::from(days_in_month(year, month));
+            if days < start + len {
+                break;
+            }
+            start += len;
+            month = if month == nb_months { 1 } else { month + 1 };
+        }
+        Self {
+            year: year as u32,
+            month,
+            day: (days - start + 1) as u8,
+        }
+    }
+    pub fn is_leap_year(self) -> bool {
+        is_leap(i64::from(self.year))
+    }
+    pub fn month_name(self) -> &'static str {
+        match (self.month, self.is_leap_year()) {
+            (12, true) => "Adar I",
+            (13, _) => "Adar II",
+            (m, _) => MONTHS[m as usize - 1],
+        }
+    }
+}
+impl fmt::Display for HebrewDate {
+    /// As `15 Nisan 5780`.
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{} {} {}", self.day, self.month_name(), self.year)
+    }
+}
+
+static MONTHS: [&str; 12] = [
+    "Nisan", "Iyar", "Sivan", "Tammuz", "Av", "Elul", "Tishrei", "Cheshvan", "Kislev", "Tevet",
+    "Shevat", "Adar",
+];
+
+/// 7 leap years of 13 months in each 19 year cycle.
+fn is_leap(year: i64) -> bool {
+    (7 * year + 1) % 19 < 7
+}
+/// Days from the epoch to the molad of Tishrei, postponed by a day when
+/// it would make Rosh Hashanah fall on a Sunday, a Wednesday or a
+/// Friday.
+fn elapsed_days(year: i64) -> i64 {
+    let months = (235 * year - 234) / 19;
+    let parts = 12_084 + 13_753 * months;
+    let days = 29 * months + parts / PARTS_PER_DAY;
+    if (3 * (days + 1)) % 7 < 3 {
+        days + 1
+    } else {
+        days
+    }
+}
+/// Postpones the new year to keep the length of the years in the
+/// allowed ranges.
+fn new_year_delay(year: i64) -> i64 {
+    let (prev, this, next) = (
+        elapsed_days(year - 1),
+        elapsed_days(year),
+        elapsed_days(year + 1),
+    );
+    if next - this == 356 {
+        2
+    } else if this - prev == 382 {
+        1
+    } else {
+        0
+    }
+}
+/// 1 Tishrei of `year` in days since 1970-01-01.
+fn new_year(year: i64) -> i64 {
+    EPOCH + elapsed_days(year) + new_year_delay(year)
+}
+fn days_in_month(year: i64, month: u8) -> u8 {
+    let days_in_year = new_year(year + 1) - new_year(year);
+    match month {
+        2 | 4 | 6 | 10 | 13 => 29,
+        12 if !is_leap(year) => 29,
+        // Cheshvan and Kislev vary to adjust the length of the year
+        8 if days_in_year % 10 != 5 => 29,
+        9 if days_in_year % 10 == 3 => 29,
+        _ => 30,
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn hebrew(year: u16, month: u8, day: u8) -> HebrewDate {
+        HebrewDate::from_date(&Date::new(year, month, day).unwrap())
+    }
+
+    #[test]
+    fn test_from_date() {
+        let date = |year, month, day| HebrewDate { year, month, day };
+        // Passover, Rosh Hashanah, Purim, Hanukkah
+        assert_eq!(hebrew(2020, 4, 9), date(5780, 1, 15));
+        assert_eq!(hebrew(2020, 9, 19), date(5781, 7, 1));
+        assert_eq!(hebrew(2020, 9, 18), date(5780, 6, 29));
+        assert_eq!(hebrew(2020, 3, 10), date(5780, 12, 14));
+        assert_eq!(hebrew(2019, 3, 21), date(5779, 13, 14));
+        assert_eq!(hebrew(2023, 12, 8), date(5784, 9, 25));
+        assert_eq!(hebrew(1970, 1, 1), date(5730, 10, 23));
+        assert_eq!(hebrew(0, 1, 1).year, 3760);
+    }
+
+    #[test]
+    fn test_display() {
+        use std::string::ToString;
+        assert_eq!(hebrew(2020, 4, 9).to_string(), "15 Nisan 5780");
+        assert_eq!(hebrew(2019, 3, 8).to_string(), "1 Adar II 5779");
+        assert_eq!(hebrew(2019, 2, 8).to_string(), "3 Adar I 5779");
+        assert_eq!(hebrew(2020, 3, 10).to_string(), "14 Adar 5780");
+    }
+}
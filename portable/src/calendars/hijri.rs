@@ -0,0 +1,105 @@
+//! The tabular Islamic calendar, with the 30 year cycle of the
+//! Kuwaiti algorithm. The religious calendar follows the observation
+//! of the moon and may differ by a day or two.
+
+use crate::datetime::Date;
+use core::fmt;
+
+/// 1 Muharram 1 (622-07-16 Julian) in days since 1970-01-01.
+const EPOCH: i64 = -492_148;
+const DAYS_PER_CYCLE: i64 = 10_631;
+
+static MONTHS: [&str; 12] = [
+    "Muharram",
+    "Safar",
+    "Rabi' I",
+    "Rabi' II",
+    "Jumada I",
+    "Jumada II",
+    "Rajab",
+    "Sha'ban",
+    "Ramadan",
+    "Shawwal",
+    "Dhu al-Qi'dah",
+    "Dhu al-Hijjah",
+];
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct HijriDate {
+    pub year: u16,
+    /// In `1..=12`.
+    pub month: u8,
+    pub day: u8,
+}
+impl HijriDate {
+    /// Returns `None` before the epoch of the calendar.
+    pub fn from_date(date: &Date) -> Option<Self> {
+        let days = date.days() - EPOCH;
+        if days < 0 {
+            return None;
+        }
+        let year = (30 * days + 10_646) / DAYS_PER_CYCLE;
+        let day_of_year = days - days_before_year(year);
+        let month = core::cmp::min(12, 2 * day_of_year / 59 + 1);
+        Some(Self {
+            year: year as u16,
+            month: month as u8,
+            day: (day_of_year - days_before_month(month) + 1) as u8,
+        })
+    }
+    pub fn is_leap_year(self) -> bool {
+        is_leap(i64::from(self.year))
+    }
+    pub fn month_name(self) -> &'static str {
+        MONTHS[self.month as usize - 1]
+    }
+}
+impl fmt::Display for HijriDate {
+    /// As `1 Ramadan 1441`.
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{} {} {}", self.day, self.month_name(), self.year)
+    }
+}
+
+/// 11 leap years of 355 days in each 30 year cycle.
+fn is_leap(year: i64) -> bool {
+    (14 + 11 * year) % 30 < 11
+}
+fn days_before_year(year: i64) -> i64 {
+    (year - 1) * 354 + (3 + 11 * year) / 30
+}
+/// The months have alternately 30 and 29 days, the last one having 30
+/// days in leap years.
+fn days_before_month(month: i64) -> i64 {
+    (59 * (month - 1) + 1) / 2
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn hijri(year: u16, month: u8, day: u8) -> Option<HijriDate> {
+        HijriDate::from_date(&Date::new(year, month, day).unwrap())
+    }
+
+    #[test]
+    fn test_from_date() {
+        let date = |year, month, day| Some(HijriDate { year, month, day });
+        assert_eq!(hijri(2019, 9, 1), date(1441, 1, 1));
+        assert_eq!(hijri(2019, 8, 31), date(1440, 12, 29));
+        assert_eq!(hijri(2020, 4, 24), date(1441, 9, 1));
+        assert_eq!(hijri(2020, 8, 19), date(1441, 12, 29));
+        assert_eq!(hijri(2021, 8, 9), date(1442, 12, 30));
+        assert_eq!(hijri(2021, 8, 10), date(1443, 1, 1));
+        assert_eq!(hijri(622, 7, 19), date(1, 1, 1));
+        assert_eq!(hijri(622, 7, 18), None);
+        assert!(date(1442, 1, 1).unwrap().is_leap_year());
+        assert!(!date(1441, 1, 1).unwrap().is_leap_year());
+    }
+
+    #[test]
+    fn test_display() {
+        use std::string::ToString;
+        assert_eq!(hijri(2020, 4, 24).unwrap().to_string(), "1 Ramadan 1441");
+    }
+}
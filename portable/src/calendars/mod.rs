@@ -0,0 +1,88 @@
+//! Calendars shown beside the Gregorian date.
+
+use crate::datetime::Date;
+use crate::locale::Locale;
+use core::fmt::{self, Write};
+
+pub mod chinese;
+pub mod hebrew;
+pub mod hijri;
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum SecondaryCalendar {
+    None,
+    Hijri,
+    Hebrew,
+    Chinese,
+}
+impl Default for SecondaryCalendar {
+    fn default() -> Self {
+        SecondaryCalendar::None
+    }
+}
+impl SecondaryCalendar {
+    pub fn next(self) -> Self {
+        use self::SecondaryCalendar::*;
+        match self {
+            None => Hijri,
+            Hijri => Hebrew,
+            Hebrew => Chinese,
+            Chinese => None,
+        }
+    }
+    pub fn prev(self) -> Self {
+        use self::SecondaryCalendar::*;
+        match self {
+            None => Chinese,
+            Hijri => None,
+            Hebrew => Hijri,
+            Chinese => Hebrew,
+        }
+    }
+    pub fn as_u8(self) -> u8 {
+        self as u8
+    }
+    pub fn try_from(u: u8) -> Option<Self> {
+        use self::SecondaryCalendar::*;
+        [None, Hijri, Hebrew, Chinese].get(u as usize).cloned()
+    }
+    pub fn name(self, locale: Locale) -> &'static str {
+        locale.strings().calendars[self as usize]
+    }
+    /// Writes `date` in this calendar, nothing for `None` or if the date
+    /// is before the beginning of the calendar.
+    pub fn write_date<W: Write>(self, date: &Date, w: &mut W) -> fmt::Result {
+        use self::SecondaryCalendar::*;
+        match self {
+            None => Ok(()),
+            Hijri => hijri::HijriDate::from_date(date).map_or(Ok(()), |d| write!(w, "{}", d)),
+            Hebrew => write!(w, "{}", hebrew::HebrewDate::from_date(date)),
+            Chinese => write!(w, "{}", chinese::ChineseDate::from_date(date)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use heapless::{consts::*, String};
+
+    #[test]
+    fn test_write_date() {
+        let date = Date::new(2020, 4, 24).unwrap();
+        let mut calendar = SecondaryCalendar::default();
+        for &expected in &["", "1 Ramadan 1441", "30 Nisan 5780", "Gengzi Rat 4-2"] {
+            let mut s: String<U32> = String::new();
+            calendar.write_date(&date, &mut s).unwrap();
+            assert_eq!(s, expected);
+            assert_eq!(
+                SecondaryCalendar::try_from(calendar.as_u8()),
+                Some(calendar)
+            );
+            calendar = calendar.next();
+        }
+        assert_eq!(calendar, SecondaryCalendar::None);
+        assert_eq!(calendar.prev().next(), calendar);
+        assert_eq!(SecondaryCalendar::Hebrew.name(Locale::French), "Hébraïque");
+    }
+}
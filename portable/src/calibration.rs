@@ -0,0 +1,185 @@
+//! Measurement of the drift of the RTC, corrected with the calibration
+//! value of the STM32 RTC.
+//!
+//! The calibration value removes 0 to 127 pulses of the 32768Hz clock
+//! every 2^20 pulses. The prescaler being shortened by two pulses, the
+//! RTC runs 61ppm fast with no pulses removed, so the correction is from
+//! -61ppm (slower) to 60ppm (faster), `NEUTRAL` being none.
+
+/// The calibration value for no correction.
+pub const NEUTRAL: u8 = 64;
+pub const MAX: u8 = 127;
+/// The correction of a step of the calibration value.
+const PPM_PER_STEP: f32 = 1_000_000. / 1_048_576.;
+/// The time needed to measure the drift, a second being 11.6ppm over a
+/// day.
+const MIN_ELAPSED: i64 = 2 * 86400;
+/// Above, the RTC is set to another time, not corrected.
+const MAX_PPM: i64 = 200;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Calibration {
+    /// The calibration value in use.
+    pub value: u8,
+    /// Since the first setting of the measurement, the time and the sum
+    /// of the drifts corrected by the settings, positive if the RTC is
+    /// fast.
+    reference: Option<(u32, i64)>,
+}
+impl Calibration {
+    pub fn new(value: u8) -> Self {
+        Self {
+            value: core::cmp::min(value, MAX),
+            reference: None,
+        }
+    }
+    /// To call when the RTC is set to `epoch` from a precise source while
+    /// it counted `rtc`. Returns the new calibration value when it
+    /// changes.
+    pub fn rtc_set(&mut self, rtc: u32, epoch: u32) -> Option<u8> {
+        let (start, drift) = match self.reference {
+            Some(reference) => reference,
+            None => {
+                self.reference = Some((epoch, 0));
+                return None;
+            }
+        };
+        let elapsed = i64::from(epoch) - i64::from(start);
+        let drift = drift + i64::from(rtc) - i64::from(epoch);
+        if elapsed <= 0 || drift.abs() > 2 + elapsed * MAX_PPM / 1_000_000 {
+            // not a drift, the measurement restarts
+            self.reference = Some((epoch, 0));
+            return None;
+        }
+        if elapsed < MIN_ELAPSED {
+            self.reference = Some((start, drift));
+            return None;
+        }
+
+        self.reference = Some((epoch, 0));
+        let ppm = drift as f32 * 1_000_000. / elapsed as f32;
+        let steps = libm::roundf(ppm / PPM_PER_STEP) as i32;
+        let value = (i32::from(self.value) + steps).max(0).min(i32::from(MAX)) as u8;
+        if value == self.value {
+            return None;
+        }
+        self.value = value;
+        Some(value)
+    }
+    /// To call when the RTC is set by hand, to the minute: its error is
+    /// not a drift, the measurement restarts at the next precise setting.
+    pub fn rtc_set_manually(&mut self) {
+        self.reference = None;
+    }
+    /// The reference of the measurement, kept in the backup domain with
+    /// the calibration value not to restart the measurement at each
+    /// reset: the time, 0 being none, and the drift.
+    pub fn backup_as_u16s(&self) -> [u16; 4] {
+        // the drift bounded by `MAX_PPM` over 136 years
+        let (start, drift) = self
+            .reference
+            .map_or((0, 0), |(start, drift)| (start, drift as i32 as u32));
+        [
+            start as u16,
+            (start >> 16) as u16,
+            drift as u16,
+            (drift >> 16) as u16,
+        ]
+    }
+    /// Restores `backup_as_u16s`.
+    pub fn set_backup(&mut self, data: [u16; 4]) {
+        let start = u32::from(data[0]) | u32::from(data[1]) << 16;
+        let drift = (u32::from(data[2]) | u32::from(data[3]) << 16) as i32;
+        self.reference = Some((start, i64::from(drift))).filter(|&(start, _)| start != 0);
+    }
+    /// The correction in ppm, positive when the RTC is slowed.
+    pub fn ppm(&self) -> f32 {
+        (f32::from(self.value) - f32::from(NEUTRAL)) * PPM_PER_STEP
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    const DAY: u32 = 86400;
+
+    #[test]
+    fn test_drift() {
+        let mut calibration = Calibration::new(NEUTRAL);
+        let start = 1_587_720_000;
+        assert_eq!(calibration.rtc_set(0, start), None);
+        // about 20ppm fast, 7 seconds in 4 days
+        let mut epoch = start + 100_000;
+        assert_eq!(calibration.rtc_set(epoch + 2, epoch), None);
+        epoch += 245_600;
+        assert_eq!(calibration.rtc_set(epoch + 5, epoch), Some(NEUTRAL + 21));
+        assert!((calibration.ppm() - 20.).abs() < 0.5);
+        // 3ppm slow during 10 days
+        epoch += 10 * DAY;
+        assert_eq!(calibration.rtc_set(epoch - 3, epoch), Some(NEUTRAL + 17));
+        // less than a step
+        epoch += 10 * DAY;
+        assert_eq!(calibration.rtc_set(epoch, epoch), None);
+        assert_eq!(calibration.value, NEUTRAL + 17);
+    }
+
+    #[test]
+    fn test_backup() {
+        let mut calibration = Calibration::new(NEUTRAL);
+        assert_eq!(calibration.backup_as_u16s(), [0; 4]);
+        let start = 1_587_720_000;
+        assert_eq!(calibration.rtc_set(0, start), None);
+        // 2 seconds slow, then a reset
+        let epoch = start + DAY;
+        assert_eq!(calibration.rtc_set(epoch - 2, epoch), None);
+        let mut restored = Calibration::new(calibration.value);
+        restored.set_backup(calibration.backup_as_u16s());
+        assert_eq!(restored, calibration);
+        // the measurement goes on, about 20ppm slow
+        let epoch = epoch + 2 * DAY;
+        assert_eq!(restored.rtc_set(epoch - 3, epoch), Some(NEUTRAL - 20));
+        restored.rtc_set_manually();
+        calibration.set_backup(restored.backup_as_u16s());
+        assert_eq!(calibration.backup_as_u16s(), [0; 4]);
+        // a reset of the backup domain
+        calibration.set_backup([0; 4]);
+        assert_eq!(calibration.rtc_set(0, epoch), None);
+    }
+
+    #[test]
+    fn test_time_change() {
+        let mut calibration = Calibration::new(NEUTRAL);
+        let start = 1_587_720_000;
+        assert_eq!(calibration.rtc_set(0, start), None);
+        // set to another time zone
+        let epoch = start + 3 * DAY;
+        assert_eq!(calibration.rtc_set(epoch + 3600, epoch), None);
+        // the measurement restarted
+        let epoch = epoch + DAY;
+        assert_eq!(calibration.rtc_set(epoch + 5, epoch), None);
+        let epoch = epoch + DAY;
+        assert_eq!(calibration.rtc_set(epoch + 5, epoch), Some(NEUTRAL + 61));
+        // clamped
+        let epoch = epoch + 2 * DAY;
+        assert_eq!(calibration.rtc_set(epoch + 30, epoch), Some(MAX));
+        assert_eq!(Calibration::new(200).value, MAX);
+    }
+
+    #[test]
+    fn test_manual_set() {
+        let mut calibration = Calibration::new(NEUTRAL);
+        let start = 1_587_720_000;
+        assert_eq!(calibration.rtc_set(0, start), None);
+        // set by hand 40 seconds off after 10 days, within `MAX_PPM`
+        calibration.rtc_set_manually();
+        // the 40 seconds are not a drift
+        let epoch = start + 11 * DAY;
+        assert_eq!(calibration.rtc_set(epoch - 40, epoch), None);
+        assert_eq!(calibration.value, NEUTRAL);
+        // the measurement restarted at the last precise setting, about
+        // 20ppm fast
+        let epoch = epoch + 3 * DAY;
+        assert_eq!(calibration.rtc_set(epoch + 5, epoch), Some(NEUTRAL + 20));
+    }
+}
@@ -0,0 +1,93 @@
+//! Month views, weeks begin on Monday as in ISO 8601.
+
+use super::{nb_days_in_month, Date, DateError};
+use heapless::{consts::*, Vec};
+
+/// A row of a month grid.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Week {
+    /// The ISO week number.
+    pub number: u8,
+    /// The days of the month, from Monday to Sunday, `None` for the
+    /// days belonging to the previous or next month.
+    pub days: [Option<u8>; 7],
+}
+
+/// Returns the 4 to 6 weeks of a month.
+pub fn month_grid(year: u16, month: u8) -> Result<Vec<Week, U6>, DateError> {
+    let first = Date::new(year, month, 1)?;
+    let nb_days = nb_days_in_month(year, month);
+    let mut weeks = Vec::new();
+    let mut week = Week {
+        number: first.iso_week(),
+        days: [None; 7],
+    };
+    for day in 1..=nb_days {
+        let dow = (first.day_of_week() as u8 + day - 1) % 7;
+        if dow == 0 && day != 1 {
+            weeks.push(week).unwrap();
+            week = Week {
+                number: Date { year, month, day }.iso_week(),
+                days: [None; 7],
+            };
+        }
+        week.days[dow as usize] = Some(day);
+    }
+    weeks.push(week).unwrap();
+    Ok(weeks)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_month_grid() {
+        let grid = month_grid(2018, 10).unwrap();
+        assert_eq!(grid.len(), 5);
+        assert_eq!(grid[0].number, 40);
+        assert_eq!(grid[0].days[0], Some(1));
+        assert_eq!(grid[3].number, 43);
+        assert_eq!(
+            grid[3].days,
+            [
+                Some(22),
+                Some(23),
+                Some(24),
+                Some(25),
+                Some(26),
+                Some(27),
+                Some(28)
+            ]
+        );
+        assert_eq!(
+            grid[4].days,
+            [Some(29), Some(30), Some(31), None, None, None, None]
+        );
+
+        // 2010-02 begins on a Monday and lasts exactly 4 weeks
+        let grid = month_grid(2010, 2).unwrap();
+        assert_eq!(grid.len(), 4);
+        assert_eq!(grid[3].days[6], Some(28));
+
+        // 2021-01 begins on a Friday in week 53 of 2020
+        let grid = month_grid(2021, 1).unwrap();
+        assert_eq!(grid.len(), 5);
+        assert_eq!(grid[0].number, 53);
+        assert_eq!(
+            grid[0].days,
+            [None, None, None, None, Some(1), Some(2), Some(3)]
+        );
+        assert_eq!(grid[1].number, 1);
+
+        // 2016-05 begins on a Sunday and spans 6 weeks
+        let grid = month_grid(2016, 5).unwrap();
+        assert_eq!(grid.len(), 6);
+        assert_eq!(grid[0].days[6], Some(1));
+        assert_eq!(grid[5].days[1], Some(31));
+
+        assert_eq!(month_grid(0, 1).unwrap()[0].number, 52);
+        assert_eq!(month_grid(65535, 12).unwrap().last().unwrap().number, 1);
+        assert_eq!(month_grid(2018, 13), Err(DateError::InvalidMonth));
+    }
+}
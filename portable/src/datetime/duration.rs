@@ -0,0 +1,68 @@
+use core::ops::{Add, Neg, Sub};
+
+/// A signed span of time, with a resolution of one second.
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Duration {
+    secs: i64,
+}
+impl Duration {
+    pub const fn from_secs(secs: i64) -> Self {
+        Self { secs }
+    }
+    pub const fn from_mins(mins: i64) -> Self {
+        Self::from_secs(mins * 60)
+    }
+    pub const fn from_hours(hours: i64) -> Self {
+        Self::from_mins(hours * 60)
+    }
+    /// Panics on overflow in debug builds, as the operators.
+    pub const fn from_days(days: i64) -> Self {
+        Self::from_hours(days * 24)
+    }
+    /// `None` on overflow.
+    pub fn checked_from_days(days: i64) -> Option<Self> {
+        days.checked_mul(86_400).map(Self::from_secs)
+    }
+    pub fn as_secs(self) -> i64 {
+        self.secs
+    }
+    pub fn as_mins(self) -> i64 {
+        self.secs / 60
+    }
+    pub fn as_hours(self) -> i64 {
+        self.secs / 3600
+    }
+    pub fn as_days(self) -> i64 {
+        self.secs / 86400
+    }
+    pub fn is_negative(self) -> bool {
+        self.secs < 0
+    }
+    pub fn abs(self) -> Self {
+        Self::from_secs(self.secs.abs())
+    }
+    pub fn checked_add(self, other: Self) -> Option<Self> {
+        self.secs.checked_add(other.secs).map(Self::from_secs)
+    }
+    pub fn checked_sub(self, other: Self) -> Option<Self> {
+        self.secs.checked_sub(other.secs).map(Self::from_secs)
+    }
+}
+impl Add for Duration {
+    type Output = Self;
+    fn add(self, other: Self) -> Self {
+        Self::from_secs(self.secs + other.secs)
+    }
+}
+impl Sub for Duration {
+    type Output = Self;
+    fn sub(self, other: Self) -> Self {
+        Self::from_secs(self.secs - other.secs)
+    }
+}
+impl Neg for Duration {
+    type Output = Self;
+    fn neg(self) -> Self {
+        Self::from_secs(-self.secs)
+    }
+}
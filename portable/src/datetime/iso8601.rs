@@ -0,0 +1,412 @@
+//! ISO 8601 and RFC 3339 formatting and parsing.
+//!
+//! Offsets are in seconds east of UTC, as in [`TimeZone::offset`].
+//!
+//! The years after 9999 are written in the expanded form of ISO 8601,
+//! with a sign and 5 digits, as `+10000-01-01`. RFC 3339 does not allow
+//! them.
+//!
+//! [`TimeZone::offset`]: ../timezone/struct.TimeZone.html#method.offset
+
+use super::{days_from_civil, first_monday, is_leap, weeks_in_year, Date, DateTime, Time};
+use core::convert::TryFrom;
+use core::fmt::Write;
+use heapless::{consts::*, String};
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Format {
+    /// `2018-10-20T16:21:41+02:00`, as in RFC 3339
+    Extended,
+    /// `20181020T162141+0200`
+    Basic,
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum ParseError {
+    /// The string does not follow the ISO 8601 syntax.
+    InvalidFormat,
+    /// The date does not exist, as 2019-02-29 or 2019-W53-1.
+    InvalidDate,
+    InvalidTime,
+    InvalidOffset,
+    MissingOffset,
+    /// The date can not be represented by a `DateTime`.
+    OutOfRange,
+}
+
+/// Formats a calendar date and time, as `2018-10-20T16:21:41Z`.
+///
+/// Without offset, the time is written as a local time.
+pub fn format(dt: &DateTime, offset: Option<i32>, format: Format) -> String<U32> {
+    let mut s = String::new();
+    let sep = date_sep(format);
+    write_year(&mut s, i32::from(dt.year()));
+    write!(s, "{}{:02}{}{:02}", sep, dt.month(), sep, dt.day()).unwrap();
+    write_time(&mut s, dt, offset, format);
+    s
+}
+
+/// Formats a week date and time, as `2018-W42-6T16:21:41Z`.
+pub fn format_week_date(dt: &DateTime, offset: Option<i32>, format: Format) -> String<U32> {
+    let mut s = String::new();
+    let sep = date_sep(format);
+    write_year(&mut s, dt.iso_year());
+    let (week, dow) = (dt.iso_week(), dt.day_of_week() as u8 + 1);
+    write!(s, "{}W{:02}{}{}", sep, week, sep, dow).unwrap();
+    write_time(&mut s, dt, offset, format);
+    s
+}
+
+/// Parses a date and an optional time and offset.
+///
+/// Calendar (`2018-10-20`), ordinal (`2018-293`) and week
+/// (`2018-W42-6`) dates are accepted, in extended or basic format.
+/// The time can be separated by `T` or a space, and fractions of
+/// seconds are truncated. The returned date time is the one written in
+/// the string, the offset is not applied.
+pub fn parse(s: &str) -> Result<(DateTime, Option<i32>), ParseError> {
+    let mut p = Parser(s.as_bytes());
+    let (date, extended) = p.date()?;
+
+    let (mut time, mut offset) = ((0, 0, 0), None);
+    if p.eat(b'T') || p.eat(b't') || p.eat(b' ') {
+        time = p.time(extended)?;
+        offset = p.offset()?;
+    }
+    if !p.0.is_empty() {
+        return Err(ParseError::InvalidFormat);
+    }
+
+    let time = Time::new(time.0, time.1, time.2).map_err(|_| ParseError::InvalidTime)?;
+    Ok((DateTime { date, time }, offset))
+}
+
+/// Parses a date time with an offset, as RFC 3339 requires, and
+/// converts it to UTC.
+pub fn parse_utc(s: &str) -> Result<DateTime, ParseError> {
+    let (dt, offset) = parse(s)?;
+    let offset = offset.ok_or(ParseError::MissingOffset)?;
+    dt.add_seconds(-i64::from(offset))
+        .ok_or(ParseError::OutOfRange)
+}
+
+/// 4 digits, else the expanded form, as `+10000`.
+fn write_year(s: &mut String<U32>, year: i32) {
+    if 0 <= year && year <= 9999 {
+        write!(s, "{:04}", year).unwrap();
+    } else {
+        write!(s, "{:+06}", year).unwrap();
+    }
+}
+
+fn date_sep(format: Format) -> &'static str {
+    match format {
+        Format::Extended => "-",
+        Format::Basic => "",
+    }
+}
+
+fn write_time(s: &mut String<U32>, dt: &DateTime, offset: Option<i32>, format: Format) {
+    let sep = match format {
+        Format::Extended => ":",
+        Format::Basic => "",
+    };
+    write!(
+        s,
+        "T{:02}{}{:02}{}{:02}",
+        dt.hour(),
+        sep,
+        dt.minute(),
+        sep,
+        dt.second()
+    )
+    .unwrap();
+    match offset {
+        None => {}
+        Some(0) => s.push('Z').unwrap(),
+        Some(offset) => {
+            let sign = if offset < 0 { '-' } else { '+' };
+            let mins = offset.abs() / 60;
+            write!(s, "{}{:02}{}{:02}", sign, mins / 60, sep, mins % 60).unwrap();
+        }
+    }
+}
+
+struct Parser<'a>(&'a [u8]);
+impl<'a> Parser<'a> {
+    fn eat(&mut self, c: u8) -> bool {
+        if self.0.first() == Some(&c) {
+            self.0 = &self.0[1..];
+            true
+        } else {
+            false
+        }
+    }
+    fn nb_digits(&self) -> usize {
+        self.0.iter().take_while(|c| c.is_ascii_digit()).count()
+    }
+    /// At most 4 digits.
+    fn digits(&mut self, n: usize) -> Result<u16, ParseError> {
+        self.number(n).map(|number| number as u16)
+    }
+    fn number(&mut self, n: usize) -> Result<i32, ParseError> {
+        if self.nb_digits() < n {
+            return Err(ParseError::InvalidFormat);
+        }
+        let (digits, rest) = self.0.split_at(n);
+        self.0 = rest;
+        Ok(digits
+            .iter()
+            .fold(0, |acc, &c| acc * 10 + i32::from(c - b'0')))
+    }
+    /// 4 digits, or a sign and 5 digits in the expanded form.
+    fn year(&mut self) -> Result<i32, ParseError> {
+        if self.eat(b'+') {
+            self.number(5)
+        } else if self.eat(b'-') {
+            self.number(5).map(|year| -year)
+        } else {
+            self.number(4)
+        }
+    }
+    /// Returns the date and if the extended format is used.
+    fn date(&mut self) -> Result<(Date, bool), ParseError> {
+        let year = self.year()?;
+        let extended = self.eat(b'-');
+        if self.eat(b'W') {
+            let week = self.digits(2)?;
+            if extended && !self.eat(b'-') {
+                return Err(ParseError::InvalidFormat);
+            }
+            let dow = self.digits(1)?;
+            let nb_weeks = weeks_in_year(year);
+            if week == 0 || week > u16::from(nb_weeks) || dow == 0 || dow > 7 {
+                return Err(ParseError::InvalidDate);
+            }
+            // the week years around the years of `DateTime`, with some of
+            // their days in them
+            let monday = match u16::try_from(year) {
+                Ok(year) => first_monday(year),
+                Err(_) if year == -1 => first_monday(0) - i64::from(nb_weeks) * 7,
+                Err(_) if year == 65536 => {
+                    first_monday(65535) + i64::from(weeks_in_year(65535)) * 7
+                }
+                Err(_) => return Err(ParseError::OutOfRange),
+            };
+            let days = monday + i64::from(week - 1) * 7 + i64::from(dow - 1);
+            let date = Date::from_days(days).ok_or(ParseError::OutOfRange)?;
+            return Ok((date, extended));
+        }
+        let year = u16::try_from(year).map_err(|_| ParseError::OutOfRange)?;
+        if self.nb_digits() == 3 {
+            let ordinal = self.digits(3)?;
+            let nb_days = if is_leap(year) { 366 } else { 365 };
+            if ordinal == 0 || ordinal > nb_days {
+                return Err(ParseError::InvalidDate);
+            }
+            let jan_1 = days_from_civil(year, 1, 1).unwrap();
+            let days = jan_1 + i64::from(ordinal) - 1;
+            return Ok((Date::from_days(days).unwrap(), extended));
+        }
+        let month = self.digits(2)?;
+        if extended && !self.eat(b'-') {
+            return Err(ParseError::InvalidFormat);
+        }
+        let day = self.digits(2)?;
+        let date = Date::new(year, month as u8, day as u8).map_err(|_| ParseError::InvalidDate)?;
+        Ok((date, extended))
+    }
+    fn time(&mut self, extended: bool) -> Result<(u8, u8, u8), ParseError> {
+        let sep = |p: &mut Self| !extended || p.eat(b':');
+        let hour = self.digits(2)?;
+        if !sep(self) {
+            return Err(ParseError::InvalidFormat);
+        }
+        let min = self.digits(2)?;
+        let mut sec = 0;
+        if self.nb_digits() > 0 || (extended && self.0.first() == Some(&b':')) {
+            if !sep(self) {
+                return Err(ParseError::InvalidFormat);
+            }
+            sec = self.digits(2)?;
+            if self.eat(b'.') || self.eat(b',') {
+                let nb_digits = self.nb_digits();
+                if nb_digits == 0 {
+                    return Err(ParseError::InvalidFormat);
+                }
+                self.0 = &self.0[nb_digits..];
+            }
+        }
+        Ok((hour as u8, min as u8, sec as u8))
+    }
+    fn offset(&mut self) -> Result<Option<i32>, ParseError> {
+        if self.eat(b'Z') || self.eat(b'z') {
+            return Ok(Some(0));
+        }
+        let sign = if self.eat(b'+') {
+            1
+        } else if self.eat(b'-') {
+            -1
+        } else {
+            return Ok(None);
+        };
+        let hour = self.digits(2)?;
+        let min = if self.eat(b':') || self.nb_digits() > 0 {
+            self.digits(2)?
+        } else {
+            0
+        };
+        if hour > 23 || min > 59 {
+            return Err(ParseError::InvalidOffset);
+        }
+        Ok(Some(sign * (i32::from(hour) * 3600 + i32::from(min) * 60)))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::datetime::DayOfWeek;
+
+    fn dt() -> DateTime {
+        DateTime::new(1540052501)
+    }
+
+    #[test]
+    fn test_format() {
+        assert_eq!(format(&dt(), None, Format::Extended), "2018-10-20T16:21:41");
+        assert_eq!(
+            format(&dt(), Some(0), Format::Extended),
+            "2018-10-20T16:21:41Z"
+        );
+        assert_eq!(
+            format(&dt(), Some(7200), Format::Extended),
+            "2018-10-20T16:21:41+02:00"
+        );
+        assert_eq!(
+            format(&dt(), Some(-(9 * 3600 + 30 * 60)), Format::Basic),
+            "20181020T162141-0930"
+        );
+        assert_eq!(
+            format_week_date(&dt(), Some(0), Format::Extended),
+            "2018-W42-6T16:21:41Z"
+        );
+        assert_eq!(
+            format_week_date(&dt(), None, Format::Basic),
+            "2018W426T162141"
+        );
+    }
+
+    #[test]
+    fn test_parse() {
+        let expected = Ok((dt(), Some(7200)));
+        assert_eq!(parse("2018-10-20T16:21:41+02:00"), expected);
+        assert_eq!(parse("2018-10-20t16:21:41.123+02:00"), expected);
+        assert_eq!(parse("2018-10-20 16:21:41,5+0200"), expected);
+        assert_eq!(parse("20181020T162141+0200"), expected);
+        assert_eq!(parse("20181020T162141+02"), expected);
+        assert_eq!(parse("2018-W42-6T16:21:41+02:00"), expected);
+        assert_eq!(parse("2018W426T162141+0200"), expected);
+        assert_eq!(parse("2018-293T16:21:41+02:00"), expected);
+        assert_eq!(parse("2018293T162141+0200"), expected);
+        assert_eq!(parse("2018-10-20T16:21:41"), Ok((dt(), None)));
+        assert_eq!(parse("2018-10-20T16:21:41Z"), Ok((dt(), Some(0))));
+        assert_eq!(parse("2018-10-20T16:21-01:30").unwrap().1, Some(-5400));
+
+        let midnight = parse("2018-10-20").unwrap().0;
+        assert_eq!((midnight.day(), midnight.hour()), (20, 0));
+        assert_eq!(midnight.day_of_week(), DayOfWeek::Saturday);
+        // 2009-W53-7 is 2010-01-03
+        let sunday = parse("2009-W53-7").unwrap().0;
+        assert_eq!(sunday.date, Date::new(2010, 1, 3).unwrap());
+        assert_eq!(sunday.day_of_week(), DayOfWeek::Sunday);
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        use super::ParseError::*;
+        assert_eq!(parse(""), Err(InvalidFormat));
+        assert_eq!(parse("2018-10-20T"), Err(InvalidFormat));
+        assert_eq!(parse("2018-1020"), Err(InvalidFormat));
+        assert_eq!(parse("2018-10-20T162141"), Err(InvalidFormat));
+        assert_eq!(parse("2018-10-20T16:21:41.Z"), Err(InvalidFormat));
+        assert_eq!(parse("2018-10-20T16:21:41 "), Err(InvalidFormat));
+        assert_eq!(parse("2019-02-29"), Err(InvalidDate));
+        assert_eq!(parse("2018-13-01"), Err(InvalidDate));
+        assert_eq!(parse("2018-W53-1"), Err(InvalidDate));
+        assert_eq!(parse("2018-W01-8"), Err(InvalidDate));
+        assert_eq!(parse("2018-366"), Err(InvalidDate));
+        assert_eq!(parse("2018-10-20T24:00:00"), Err(InvalidTime));
+        assert_eq!(parse("2018-10-20T23:60:00"), Err(InvalidTime));
+        assert_eq!(parse("2018-10-20T23:59:60Z"), Err(InvalidTime));
+        assert_eq!(parse("2018-10-20T00:00:00+24:00"), Err(InvalidOffset));
+    }
+
+    #[test]
+    fn test_expanded_years() {
+        let last = DateTime::from_timestamp(253402300799).unwrap();
+        let first = DateTime::from_timestamp(253402300800).unwrap();
+        assert_eq!(
+            format(&last, Some(0), Format::Extended),
+            "9999-12-31T23:59:59Z"
+        );
+        assert_eq!(
+            format(&first, Some(0), Format::Extended),
+            "+10000-01-01T00:00:00Z"
+        );
+        assert_eq!(format(&first, None, Format::Basic), "+100000101T000000");
+        // 9999-12-31 is a Friday, in the last week of 9999
+        assert_eq!(
+            format_week_date(&last, None, Format::Extended),
+            "9999-W52-5T23:59:59"
+        );
+        assert_eq!(
+            format_week_date(&first, None, Format::Extended),
+            "9999-W52-6T00:00:00"
+        );
+        assert_eq!(parse("+10000-01-01"), Ok((first, None)));
+        assert_eq!(parse("+09999-12-31T23:59:59").unwrap().0, last);
+        assert_eq!(parse("+99999-01-01"), Err(ParseError::OutOfRange));
+        assert_eq!(parse("-00001-01-01"), Err(ParseError::OutOfRange));
+        assert_eq!(parse("+1000-01-01"), Err(ParseError::InvalidFormat));
+        assert_eq!(parse("10000-01-01"), Err(ParseError::InvalidFormat));
+    }
+
+    #[test]
+    fn test_parse_utc() {
+        assert_eq!(parse_utc("2018-10-20T18:21:41+02:00"), Ok(dt()));
+        assert_eq!(parse_utc("2018-10-20T16:21:41Z"), Ok(dt()));
+        assert_eq!(
+            parse_utc("2018-10-20T16:21:41"),
+            Err(ParseError::MissingOffset)
+        );
+        assert_eq!(
+            parse_utc("0000-01-01T00:59:59+01:00"),
+            Err(ParseError::OutOfRange)
+        );
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let timestamps = [
+            -62167219200, // 0000-01-01, in the week year -1
+            -62162035200, // 0000-03-01
+            -1,
+            0,
+            951782400,
+            1540052501,
+            1546214400,
+            i64::from(core::u32::MAX),
+            253402300799,  // 9999-12-31T23:59:59
+            253402300800,  // 10000-01-01
+            2005949145599, // 65535-12-31T23:59:59, in the week year 65536
+        ];
+        for &timestamp in &timestamps {
+            let dt = DateTime::from_timestamp(timestamp).unwrap();
+            for &f in &[Format::Extended, Format::Basic] {
+                assert_eq!(parse(&format(&dt, Some(0), f)), Ok((dt, Some(0))));
+                assert_eq!(parse(&format_week_date(&dt, None, f)), Ok((dt, None)));
+            }
+        }
+    }
+}
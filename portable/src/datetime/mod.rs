@@ -0,0 +1,628 @@
+pub mod calendar;
+mod duration;
+pub mod iso8601;
+pub mod timezone;
+
+pub use self::duration::Duration;
+pub use self::timezone::TimeZone;
+use crate::locale::Locale;
+
+static DAYS_IN_MONTH: [u32; 12] = [31, 28, 31, 30, 31, 30, 31, 31, 30, 31, 30, 31];
+static DAYS_IN_MONTH_LEAP_YEAR: [u32; 12] = [31, 29, 31, 30, 31, 30, 31, 31, 30, 31, 30, 31];
+fn days_in_month(is_leap_year: bool) -> &'static [u32; 12] {
+    if is_leap_year {
+        &DAYS_IN_MONTH_LEAP_YEAR
+    } else {
+        &DAYS_IN_MONTH
+    }
+}
+fn is_leap(year: u16) -> bool {
+    if year % 4 != 0 {
+        false
+    } else if year % 100 != 0 {
+        true
+    } else {
+        year % 400 == 0
+    }
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum DayOfWeek {
+    Monday,
+    Tuesday,
+    Wednesday,
+    Thursday,
+    Friday,
+    Saturday,
+    Sunday,
+}
+impl DayOfWeek {
+    pub fn from_days_since_epoch(days: u32) -> DayOfWeek {
+        Self::from_days(i64::from(days))
+    }
+    /// Days since 1970-01-01, negative before.
+    pub fn from_days(days: i64) -> DayOfWeek {
+        use self::DayOfWeek::*;
+        match days.rem_euclid(7) {
+            4 => Monday,
+            5 => Tuesday,
+            6 => Wednesday,
+            0 => Thursday,
+            1 => Friday,
+            2 => Saturday,
+            3 => Sunday,
+            _ => unreachable!(),
+        }
+    }
+    pub fn next(self) -> Self {
+        use self::DayOfWeek::*;
+        match self {
+            Monday => Tuesday,
+            Tuesday => Wednesday,
+            Wednesday => Thursday,
+            Thursday => Friday,
+            Friday => Saturday,
+            Saturday => Sunday,
+            Sunday => Monday,
+        }
+    }
+    /// The name of the day, as `Monday`, in `locale`.
+    pub fn localized(self, locale: Locale) -> &'static str {
+        locale.day_of_week(self)
+    }
+}
+impl ::core::fmt::Display for DayOfWeek {
+    fn fmt(&self, f: &mut ::core::fmt::Formatter) -> Result<(), ::core::fmt::Error> {
+        f.write_str(self.localized(Locale::English))
+    }
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum DateError {
+    InvalidMonth,
+    /// The day does not exist in this month.
+    InvalidDay,
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum TimeError {
+    InvalidHour,
+    InvalidMinute,
+    InvalidSecond,
+}
+
+/// A valid date of the gregorian calendar, from year 0 to 65535.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Date {
+    year: u16,
+    month: u8,
+    day: u8,
+}
+impl Date {
+    pub fn new(year: u16, month: u8, day: u8) -> Result<Date, DateError> {
+        if month == 0 || month > 12 {
+            return Err(DateError::InvalidMonth);
+        }
+        if day == 0 || day > nb_days_in_month(year, month) {
+            return Err(DateError::InvalidDay);
+        }
+        Ok(Date { year, month, day })
+    }
+    /// From days since 1970-01-01, negative before. `None` if the year
+    /// is not in `0..=65535`.
+    pub fn from_days(days: i64) -> Option<Date> {
+        let (year, month, day) = civil_from_days(days)?;
+        Some(Date { year, month, day })
+    }
+    /// Days since 1970-01-01, negative before.
+    pub fn days(self) -> i64 {
+        days_from_civil(self.year, self.month, self.day).unwrap()
+    }
+    pub fn year(self) -> u16 {
+        self.year
+    }
+    pub fn month(self) -> u8 {
+        self.month
+    }
+    pub fn day(self) -> u8 {
+        self.day
+    }
+    pub fn day_of_week(self) -> DayOfWeek {
+        DayOfWeek::from_days(self.days())
+    }
+    pub fn is_leap_year(self) -> bool {
+        is_leap(self.year)
+    }
+    pub fn days_in_month(self) -> u8 {
+        nb_days_in_month(self.year, self.month)
+    }
+    /// Day of the year, from 1 to 366.
+    pub fn ordinal(self) -> u16 {
+        (self.days() - days_from_civil(self.year, 1, 1).unwrap() + 1) as u16
+    }
+    /// The ISO 8601 week-numbering year. It differs from `year` when
+    /// the first days of January are in the last week of the previous
+    /// year, or the last days of December in week 1 of the next year.
+    pub fn iso_year(self) -> i32 {
+        self.iso_year_week().0
+    }
+    /// The ISO 8601 week number, from 1 to 53. Weeks begin on Monday
+    /// and week 1 is the one containing January 4th.
+    pub fn iso_week(self) -> u8 {
+        self.iso_year_week().1
+    }
+    fn iso_year_week(self) -> (i32, u8) {
+        let week = (i32::from(self.ordinal()) - (self.day_of_week() as i32 + 1) + 10) / 7;
+        let year = i32::from(self.year);
+        if week < 1 {
+            (year - 1, weeks_in_year(year - 1))
+        } else if week > i32::from(weeks_in_year(year)) {
+            (year + 1, 1)
+        } else {
+            (year, week as u8)
+        }
+    }
+}
+fn nb_days_in_month(year: u16, month: u8) -> u8 {
+    days_in_month(is_leap(year))[month as usize - 1] as u8
+}
+fn weeks_in_year(year: i32) -> u8 {
+    // 400 years are exactly 20871 weeks, shift to avoid negative years
+    let p = |y: i32| (y + y / 4 - y / 100 + y / 400) % 7;
+    let y = year + 400;
+    if p(y) == 4 || p(y - 1) == 3 {
+        53
+    } else {
+        52
+    }
+}
+/// Returns the days since epoch of the Monday of the first ISO week of
+/// `year`.
+fn first_monday(year: u16) -> i64 {
+    let jan_4 = days_from_civil(year, 1, 4).unwrap();
+    jan_4 - DayOfWeek::from_days(jan_4) as i64
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Time {
+    hour: u8,
+    min: u8,
+    sec: u8,
+}
+impl Time {
+    pub const MIDNIGHT: Time = Time {
+        hour: 0,
+        min: 0,
+        sec: 0,
+    };
+    pub fn new(hour: u8, min: u8, sec: u8) -> Result<Time, TimeError> {
+        if hour > 23 {
+            Err(TimeError::InvalidHour)
+        } else if min > 59 {
+            Err(TimeError::InvalidMinute)
+        } else if sec > 59 {
+            Err(TimeError::InvalidSecond)
+        } else {
+            Ok(Time { hour, min, sec })
+        }
+    }
+    /// From seconds since midnight, `None` if not in `0..86400`.
+    pub fn from_seconds(secs: u32) -> Option<Time> {
+        if secs >= 86400 {
+            return None;
+        }
+        Some(Time {
+            hour: (secs / 60 / 60) as u8,
+            min: (secs / 60 % 60) as u8,
+            sec: (secs % 60) as u8,
+        })
+    }
+    /// Seconds since midnight.
+    pub fn seconds(self) -> u32 {
+        u32::from(self.hour) * 3600 + u32::from(self.min) * 60 + u32::from(self.sec)
+    }
+    pub fn hour(self) -> u8 {
+        self.hour
+    }
+    pub fn minute(self) -> u8 {
+        self.min
+    }
+    pub fn second(self) -> u8 {
+        self.sec
+    }
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct DateTime {
+    pub date: Date,
+    pub time: Time,
+}
+impl DateTime {
+    /// From the value of the 32 bit RTC counter, see `from_timestamp`.
+    pub fn new(epoch: u32) -> DateTime {
+        Self::from_timestamp(i64::from(epoch)).unwrap()
+    }
+    /// `None` before 1970 or after 2106-02-07 06:28:15, see `timestamp`.
+    pub fn to_epoch(self) -> Option<u32> {
+        let timestamp = self.timestamp();
+        if timestamp < 0 || timestamp > i64::from(core::u32::MAX) {
+            return None;
+        }
+        Some(timestamp as u32)
+    }
+    /// From seconds since 1970-01-01 00:00:00, negative before. `None` if
+    /// the year is not in `0..=65535`.
+    pub fn from_timestamp(timestamp: i64) -> Option<DateTime> {
+        let date = Date::from_days(timestamp.div_euclid(86400))?;
+        let time = Time::from_seconds(timestamp.rem_euclid(86400) as u32)?;
+        Some(DateTime { date, time })
+    }
+    pub fn timestamp(self) -> i64 {
+        self.date.days() * 86400 + i64::from(self.time.seconds())
+    }
+    pub fn year(self) -> u16 {
+        self.date.year
+    }
+    pub fn month(self) -> u8 {
+        self.date.month
+    }
+    pub fn day(self) -> u8 {
+        self.date.day
+    }
+    pub fn day_of_week(self) -> DayOfWeek {
+        self.date.day_of_week()
+    }
+    pub fn ordinal(self) -> u16 {
+        self.date.ordinal()
+    }
+    pub fn iso_year(self) -> i32 {
+        self.date.iso_year()
+    }
+    pub fn iso_week(self) -> u8 {
+        self.date.iso_week()
+    }
+    pub fn hour(self) -> u8 {
+        self.time.hour
+    }
+    pub fn minute(self) -> u8 {
+        self.time.min
+    }
+    pub fn second(self) -> u8 {
+        self.time.sec
+    }
+    /// Returns `None` if the result is not representable.
+    pub fn add_duration(&self, duration: Duration) -> Option<DateTime> {
+        let timestamp = self.timestamp().checked_add(duration.as_secs())?;
+        DateTime::from_timestamp(timestamp)
+    }
+    pub fn add_seconds(&self, secs: i64) -> Option<DateTime> {
+        self.add_duration(Duration::from_secs(secs))
+    }
+    pub fn add_days(&self, days: i64) -> Option<DateTime> {
+        self.add_duration(Duration::checked_from_days(days)?)
+    }
+    /// Adds calendar months, the day is clamped to the end of the
+    /// month: 2019-01-31 plus one month is 2019-02-28.
+    pub fn add_months(&self, months: i32) -> Option<DateTime> {
+        let months = i64::from(self.year()) * 12 + i64::from(self.month()) - 1 + i64::from(months);
+        if months < 0 || months / 12 > i64::from(core::u16::MAX) {
+            return None;
+        }
+        let year = (months / 12) as u16;
+        let month = (months % 12) as u8 + 1;
+        let day = core::cmp::min(self.day(), nb_days_in_month(year, month));
+        Some(DateTime {
+            date: Date { year, month, day },
+            time: self.time,
+        })
+    }
+    /// `self - other`, negative if `other` is after `self`.
+    pub fn sub(&self, other: &DateTime) -> Duration {
+        Duration::from_secs(self.timestamp() - other.timestamp())
+    }
+    /// The time elapsed from `earlier` to `self`, `None` if `earlier` is
+    /// after `self`.
+    pub fn duration_since(&self, earlier: &DateTime) -> Option<Duration> {
+        Some(self.sub(earlier)).filter(|d| !d.is_negative())
+    }
+}
+
+// Closed-form conversions from
+// http://howardhinnant.github.io/date_algorithms.html
+//
+// The computations are done on years beginning on March 1st, so that
+// the leap day is the last day of the year, and on eras of 400 years
+// (146097 days), the period of the gregorian calendar. Only the era
+// needs 64 bit arithmetic, everything inside an era fits in a `u32`.
+
+/// Days between 0000-03-01 and 1970-01-01.
+const DAYS_TO_EPOCH: i64 = 719_468;
+const DAYS_PER_ERA: i64 = 146_097;
+
+/// Returns `None` if the year is not in `0..=65535`.
+fn civil_from_days(days: i64) -> Option<(u16, u8, u8)> {
+    let z = days.checked_add(DAYS_TO_EPOCH)?;
+    let era = z.div_euclid(DAYS_PER_ERA);
+    let (yoe, month, day) = civil_from_day_of_era(z.rem_euclid(DAYS_PER_ERA) as u32);
+    let year = era.checked_mul(400)? + i64::from(yoe);
+    if year < 0 || year > i64::from(core::u16::MAX) {
+        return None;
+    }
+    Some((year as u16, month, day))
+}
+/// Returns the year of era, month and day for a day of era in `0..146097`.
+fn civil_from_day_of_era(doe: u32) -> (u32, u8, u8) {
+    let yoe = (doe - doe / 1460 + doe / 36524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = (doy - (153 * mp + 2) / 5 + 1) as u8;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 } as u8;
+    (yoe + (month <= 2) as u32, month, day)
+}
+/// Returns the day of era in `0..146097` for a year of era in `0..400`,
+/// with January and February belonging to the previous year.
+fn day_of_era_from_civil(yoe: u32, month: u8, day: u8) -> u32 {
+    let month = u32::from(month);
+    let mp = if month > 2 { month - 3 } else { month + 9 };
+    let doy = (153 * mp + 2) / 5 + u32::from(day) - 1;
+    yoe * 365 + yoe / 4 - yoe / 100 + doy
+}
+/// Days since 1970-01-01, negative before.
+fn days_from_civil(year: u16, month: u8, day: u8) -> Option<i64> {
+    if month == 0 || month > 12 || day == 0 {
+        return None;
+    }
+    let year = i64::from(year) - (month <= 2) as i64;
+    let era = year.div_euclid(400);
+    let doe = day_of_era_from_civil(year.rem_euclid(400) as u32, month, day);
+    Some(era * DAYS_PER_ERA + i64::from(doe) - DAYS_TO_EPOCH)
+}
+impl ::core::fmt::Display for DateTime {
+    fn fmt(&self, f: &mut ::core::fmt::Formatter) -> Result<(), ::core::fmt::Error> {
+        write!(
+            f,
+            "{:04}-{:02}-{:02} {:02}:{:02}:{:02} ({})",
+            self.year(),
+            self.month(),
+            self.day(),
+            self.hour(),
+            self.minute(),
+            self.second(),
+            self.day_of_week(),
+        )
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn dt(year: u16, month: u8, day: u8, hour: u8, min: u8, sec: u8) -> DateTime {
+        DateTime {
+            date: Date::new(year, month, day).unwrap(),
+            time: Time::new(hour, min, sec).unwrap(),
+        }
+    }
+    fn epoch() -> DateTime {
+        dt(1970, 1, 1, 0, 0, 0)
+    }
+    fn end_of_time() -> DateTime {
+        dt(2106, 2, 7, 6, 28, 15)
+    }
+
+    #[test]
+    fn test_epoch() {
+        assert_eq!(DateTime::new(0), epoch());
+        assert_eq!(epoch().day_of_week(), DayOfWeek::Thursday);
+        assert_eq!(DateTime::new(core::u32::MAX), end_of_time());
+        assert_eq!(end_of_time().day_of_week(), DayOfWeek::Sunday);
+        assert_eq!(DateTime::new(1540052501), dt(2018, 10, 20, 16, 21, 41));
+        assert_eq!(DateTime::new(1540052501).day_of_week(), DayOfWeek::Saturday);
+        assert_eq!(epoch().to_epoch(), Some(0));
+        assert_eq!(end_of_time().to_epoch(), Some(core::u32::MAX));
+        assert_eq!(dt(2106, 2, 7, 6, 28, 16).to_epoch(), None);
+        assert_eq!(dt(1969, 12, 31, 23, 59, 59).to_epoch(), None);
+    }
+
+    #[test]
+    fn test_display() {
+        use std::string::ToString;
+        let dt = DateTime::new(1540052501);
+        assert_eq!(dt.to_string(), "2018-10-20 16:21:41 (Saturday)");
+        assert_eq!(dt.day_of_week().localized(Locale::Spanish), "sábado");
+    }
+
+    #[test]
+    fn test_validation() {
+        assert_eq!(Date::new(2019, 0, 1), Err(DateError::InvalidMonth));
+        assert_eq!(Date::new(2019, 13, 1), Err(DateError::InvalidMonth));
+        assert_eq!(Date::new(2019, 1, 0), Err(DateError::InvalidDay));
+        assert_eq!(Date::new(2019, 2, 29), Err(DateError::InvalidDay));
+        assert_eq!(Date::new(2019, 4, 31), Err(DateError::InvalidDay));
+        assert!(Date::new(2020, 2, 29).is_ok());
+        assert!(Date::new(2000, 2, 29).is_ok());
+        assert_eq!(Date::new(2100, 2, 29), Err(DateError::InvalidDay));
+        assert_eq!(Date::new(2019, 2, 1).unwrap().days_in_month(), 28);
+
+        assert_eq!(Time::new(24, 0, 0), Err(TimeError::InvalidHour));
+        assert_eq!(Time::new(23, 60, 0), Err(TimeError::InvalidMinute));
+        assert_eq!(Time::new(23, 59, 60), Err(TimeError::InvalidSecond));
+        assert_eq!(Time::new(0, 0, 0), Ok(Time::MIDNIGHT));
+        assert_eq!(Time::new(23, 59, 59).unwrap().seconds(), 86399);
+        assert_eq!(Time::from_seconds(86399), Time::new(23, 59, 59).ok());
+        assert_eq!(Time::from_seconds(86400), None);
+    }
+
+    #[test]
+    fn test_timestamp() {
+        let first = dt(0, 1, 1, 0, 0, 0);
+        let last = dt(65535, 12, 31, 23, 59, 59);
+        let before_epoch = dt(1969, 7, 20, 20, 17, 40);
+        assert_eq!(first.day_of_week(), DayOfWeek::Saturday);
+        assert_eq!(last.day_of_week(), DayOfWeek::Tuesday);
+        assert_eq!(before_epoch.day_of_week(), DayOfWeek::Sunday);
+        assert_eq!(DateTime::from_timestamp(-62167219200), Some(first));
+        assert_eq!(first.timestamp(), -62167219200);
+        assert_eq!(DateTime::from_timestamp(-62167219201), None);
+        assert_eq!(DateTime::from_timestamp(2005949145599), Some(last));
+        assert_eq!(last.timestamp(), 2005949145599);
+        assert_eq!(DateTime::from_timestamp(2005949145600), None);
+        assert_eq!(DateTime::from_timestamp(core::i64::MIN), None);
+        assert_eq!(DateTime::from_timestamp(core::i64::MAX), None);
+        assert_eq!(DateTime::from_timestamp(-14182940), Some(before_epoch));
+        assert_eq!(before_epoch.timestamp(), -14182940);
+        assert_eq!(before_epoch.to_epoch(), None);
+        assert_eq!(
+            DateTime::from_timestamp(i64::from(core::u32::MAX) + 1)
+                .unwrap()
+                .second(),
+            16
+        );
+        // the leap years every 400 years
+        for &(year, day) in &[(1600, 29), (1700, 28), (2000, 29), (2100, 28), (2400, 29)] {
+            let dt = dt(year, 3, 1, 0, 0, 0);
+            assert_eq!(dt.add_days(-1).unwrap().day(), day);
+        }
+    }
+
+    /// The historical implementation, looping over the years.
+    fn naive_days_since_epoch(year: u16, month: u8, day: u8) -> u32 {
+        let mut days = u32::from(day) - 1;
+        for y in 1970..year {
+            days += if is_leap(y) { 366 } else { 365 };
+        }
+        for &m in days_in_month(is_leap(year)).iter().take(month as usize - 1) {
+            days += m;
+        }
+        days
+    }
+
+    #[test]
+    fn test_every_day() {
+        let (mut year, mut month, mut day) = (1970, 1, 1);
+        let mut day_of_week = DayOfWeek::Thursday;
+        for days in 0..=core::u32::MAX / 86400 {
+            let dt = DateTime::new(days * 86400);
+            assert_eq!(dt, self::dt(year, month, day, 0, 0, 0));
+            assert_eq!(dt.day_of_week(), day_of_week);
+            assert_eq!(
+                days,
+                naive_days_since_epoch(dt.year(), dt.month(), dt.day()),
+                "{}",
+                dt
+            );
+            assert_eq!(dt.to_epoch(), Some(days * 86400));
+
+            day_of_week = day_of_week.next();
+            day += 1;
+            if u32::from(day) > days_in_month(is_leap(year))[month as usize - 1] {
+                day = 1;
+                month += 1;
+                if month > 12 {
+                    month = 1;
+                    year += 1;
+                }
+            }
+        }
+    }
+
+    // takes a few minutes, run it with `cargo test --release -- --ignored`
+    #[test]
+    #[ignore]
+    fn test_round_trip_exhaustive() {
+        for epoch in 0..=core::u32::MAX {
+            assert_eq!(DateTime::new(epoch).to_epoch(), Some(epoch));
+        }
+    }
+
+    /// A sample of `test_round_trip_exhaustive`, with prime steps not to
+    /// fall on the same time of the day, and of the whole range of the
+    /// years.
+    #[test]
+    fn test_round_trip() {
+        for epoch in (0..=core::u32::MAX).step_by(99_991) {
+            assert_eq!(DateTime::new(epoch).to_epoch(), Some(epoch));
+        }
+        for timestamp in (-62167219200..=2005949145599).step_by(10_000_019) {
+            let dt = DateTime::from_timestamp(timestamp).unwrap();
+            assert_eq!(dt.timestamp(), timestamp, "{}", dt);
+        }
+    }
+
+    #[test]
+    fn test_arithmetic() {
+        let dt = DateTime::new(1540052501);
+        assert_eq!(dt.add_seconds(19).unwrap().minute(), 22);
+        assert_eq!(dt.add_seconds(-41).unwrap().second(), 0);
+        assert_eq!(
+            dt.add_days(12).unwrap().to_epoch(),
+            Some(1540052501 + 12 * 86400)
+        );
+        assert_eq!(dt.add_days(12).unwrap().month(), 11);
+        assert_eq!(dt.add_days(12).unwrap().day(), 1);
+        assert_eq!(dt.add_days(-1).unwrap().day_of_week(), DayOfWeek::Friday);
+        assert_eq!(epoch().add_seconds(-1).unwrap().year(), 1969);
+        assert_eq!(end_of_time().add_seconds(1).unwrap().to_epoch(), None);
+        assert_eq!(dt.add_days(i64::MAX), None);
+        assert_eq!(dt.add_days(i64::MIN), None);
+        assert_eq!(end_of_time().add_seconds(-1).unwrap().second(), 14);
+        let last = DateTime::from_timestamp(2005949145599).unwrap();
+        assert_eq!(last.add_seconds(1), None);
+
+        let later = dt.add_duration(Duration::from_mins(90)).unwrap();
+        assert_eq!(later.sub(&dt), Duration::from_mins(90));
+        assert_eq!(dt.sub(&later), Duration::from_mins(-90));
+        assert_eq!(later.duration_since(&dt), Some(Duration::from_secs(5400)));
+        assert_eq!(dt.duration_since(&later), None);
+        assert_eq!(dt.duration_since(&dt), Some(Duration::default()));
+    }
+
+    #[test]
+    fn test_add_months() {
+        let dt = dt(2019, 1, 31, 7, 25, 0);
+        let feb = dt.add_months(1).unwrap();
+        assert_eq!(feb, self::dt(2019, 2, 28, 7, 25, 0));
+        assert_eq!(feb.day_of_week(), DayOfWeek::Thursday);
+        let feb = dt.add_months(13).unwrap();
+        assert_eq!(feb.date, Date::new(2020, 2, 29).unwrap());
+        let dec = dt.add_months(-1).unwrap();
+        assert_eq!(dec.date, Date::new(2018, 12, 31).unwrap());
+        let apr = dt.add_months(-9).unwrap();
+        assert_eq!(apr.date, Date::new(2018, 4, 30).unwrap());
+        assert_eq!(dt.add_months(0), Some(dt));
+        let first = DateTime::from_timestamp(-62167219200).unwrap();
+        assert_eq!(first.add_months(-1), None);
+        assert_eq!(epoch().add_months(-1).unwrap().year(), 1969);
+        assert_eq!(end_of_time().add_months(1).unwrap().month(), 3);
+    }
+
+    #[test]
+    fn test_ordinal() {
+        assert_eq!(epoch().ordinal(), 1);
+        assert_eq!(dt(2018, 10, 20, 0, 0, 0).ordinal(), 293);
+        assert_eq!(dt(2019, 12, 31, 0, 0, 0).ordinal(), 365);
+        assert_eq!(dt(2020, 12, 31, 0, 0, 0).ordinal(), 366);
+        assert_eq!(dt(65535, 12, 31, 0, 0, 0).ordinal(), 365);
+    }
+
+    #[test]
+    fn test_iso_week() {
+        let week = |y, m, d| {
+            let date = Date::new(y, m, d).unwrap();
+            (date.iso_year(), date.iso_week())
+        };
+        // 2008-12-29 is a Monday in week 1 of 2009
+        assert_eq!(week(2008, 12, 29), (2009, 1));
+        // 2010-01-03 is a Sunday in week 53 of 2009
+        assert_eq!(week(2010, 1, 3), (2009, 53));
+        assert_eq!(week(2010, 1, 4), (2010, 1));
+        assert_eq!(week(2018, 10, 20), (2018, 42));
+        assert_eq!(week(2018, 12, 31), (2019, 1));
+        // 0000-01-01 is a Saturday
+        assert_eq!(week(0, 1, 1), (-1, 52));
+        assert_eq!(week(65535, 12, 31), (65536, 1));
+        assert_eq!(weeks_in_year(2015), 53);
+        assert_eq!(weeks_in_year(2018), 52);
+        assert_eq!(weeks_in_year(2020), 53);
+    }
+}
@@ -0,0 +1,457 @@
+use super::{days_from_civil, days_in_month, is_leap, DateTime};
+use heapless::{consts::*, String};
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum ParseError {
+    InvalidName,
+    InvalidOffset,
+    InvalidRule,
+    TrailingCharacters,
+}
+
+/// A time zone described by a POSIX TZ string, for example
+/// `CET-1CEST,M3.5.0,M10.5.0/3`.
+///
+/// Offsets are stored in seconds east of UTC, i.e. with the opposite
+/// sign of the TZ string.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TimeZone {
+    std_name: String<U8>,
+    std_offset: i32,
+    dst: Option<Dst>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Dst {
+    name: String<U8>,
+    offset: i32,
+    start: Transition,
+    end: Transition,
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+struct Transition {
+    rule: Rule,
+    /// seconds since local midnight, can be negative or more than a day
+    time: i32,
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+enum Rule {
+    /// `Jn`: day of year in 1..=365, February 29 is never counted
+    Julian1(u16),
+    /// `n`: zero based day of year in 0..=365
+    Julian0(u16),
+    /// `Mm.w.d`: day `d` (0 is Sunday) of week `w` (5 is the last one)
+    /// of month `m`
+    MonthWeekDay(u8, u8, u8),
+}
+
+impl Default for TimeZone {
+    fn default() -> Self {
+        Self::utc()
+    }
+}
+
+impl TimeZone {
+    pub fn utc() -> Self {
+        let mut std_name = String::new();
+        std_name.push_str("UTC").unwrap();
+        Self {
+            std_name,
+            std_offset: 0,
+            dst: None,
+        }
+    }
+    pub fn parse(s: &str) -> Result<Self, ParseError> {
+        let mut p = Parser(s.as_bytes());
+        let std_name = p.name()?;
+        let std_offset = -p.offset().ok_or(ParseError::InvalidOffset)?;
+        let dst = if p.is_empty() {
+            None
+        } else {
+            let name = p.name()?;
+            let offset = match p.peek() {
+                Some(b',') | None => std_offset + 3600,
+                _ => -p.offset().ok_or(ParseError::InvalidOffset)?,
+            };
+            let (start, end) = if p.is_empty() {
+                // same default as glibc: the US rules
+                let start = Transition {
+                    rule: Rule::MonthWeekDay(3, 2, 0),
+                    time: 2 * 3600,
+                };
+                let end = Transition {
+                    rule: Rule::MonthWeekDay(11, 1, 0),
+                    time: 2 * 3600,
+                };
+                (start, end)
+            } else {
+                let start = p.transition().ok_or(ParseError::InvalidRule)?;
+                let end = p.transition().ok_or(ParseError::InvalidRule)?;
+                (start, end)
+            };
+            Some(Dst {
+                name,
+                offset,
+                start,
+                end,
+            })
+        };
+        if !p.is_empty() {
+            return Err(ParseError::TrailingCharacters);
+        }
+        Ok(Self {
+            std_name,
+            std_offset,
+            dst,
+        })
+    }
+    /// Offset from UTC, in seconds, at the given UTC timestamp.
+    pub fn offset(&self, timestamp: i64) -> i32 {
+        match &self.dst {
+            Some(dst) if self.is_dst(timestamp) => dst.offset,
+            _ => self.std_offset,
+        }
+    }
+    /// Always `false` out of the years `0..=65535`.
+    pub fn is_dst(&self, timestamp: i64) -> bool {
+        let dst = match &self.dst {
+            None => return false,
+            Some(dst) => dst,
+        };
+        let local = timestamp.checked_add(i64::from(self.std_offset));
+        let year = match local.and_then(DateTime::from_timestamp) {
+            Some(local) => local.year(),
+            None => return false,
+        };
+        let start = dst.start.local_epoch(year) - i64::from(self.std_offset);
+        let end = dst.end.local_epoch(year) - i64::from(dst.offset);
+        if start < end {
+            start <= timestamp && timestamp < end
+        } else {
+            // southern hemisphere: DST at the start and at the end of the year
+            !(end <= timestamp && timestamp < start)
+        }
+    }
+    /// Abbreviation of the time zone at the given UTC timestamp, as `CET`
+    /// or `CEST`.
+    pub fn name(&self, timestamp: i64) -> &str {
+        match &self.dst {
+            Some(dst) if self.is_dst(timestamp) => &dst.name,
+            _ => &self.std_name,
+        }
+    }
+    /// Converts an UTC timestamp to local time, `None` if the local year
+    /// is not in `0..=65535`.
+    pub fn to_local(&self, timestamp: i64) -> Option<DateTime> {
+        DateTime::from_timestamp(timestamp.checked_add(i64::from(self.offset(timestamp)))?)
+    }
+    /// Converts a local time to an UTC timestamp.
+    ///
+    /// When the local time occurs twice, the first occurrence (during
+    /// DST) is returned. When it does not exist, it is interpreted in
+    /// standard time.
+    pub fn to_utc(&self, local: &DateTime) -> i64 {
+        let local = local.timestamp();
+        if let Some(dst) = &self.dst {
+            let utc = local - i64::from(dst.offset);
+            if self.is_dst(utc) {
+                return utc;
+            }
+        }
+        local - i64::from(self.std_offset)
+    }
+    /// The first UTC timestamp at which the local clock shows `local` or
+    /// later: as `to_utc`, except for a local time skipped by a change
+    /// of offset, reached at the change.
+    pub fn reached_at(&self, local: &DateTime) -> i64 {
+        let utc = self.to_utc(local);
+        if self.to_local(utc).map_or(true, |l| l == *local) {
+            return utc;
+        }
+        let is_reached = |t: i64| self.to_local(t).map_or(true, |l| l >= *local);
+        let dst_offset = self.dst.as_ref().map_or(self.std_offset, |dst| dst.offset);
+        let max_offset = core::cmp::max(self.std_offset, dst_offset);
+        // the clock jumping over `local` somewhere in `(before, after]`
+        let (mut before, mut after) = (local.timestamp() - i64::from(max_offset), utc);
+        while after - before > 1 {
+            let middle = before + (after - before) / 2;
+            if is_reached(middle) {
+                after = middle;
+            } else {
+                before = middle;
+            }
+        }
+        after
+    }
+}
+
+impl Transition {
+    fn local_epoch(self, year: u16) -> i64 {
+        let leap = is_leap(year);
+        let jan_1 = days_from_civil(year, 1, 1).unwrap();
+        let day = match self.rule {
+            Rule::Julian1(n) => {
+                let n = i64::from(n) - 1;
+                if leap && n >= 59 {
+                    n + 1
+                } else {
+                    n
+                }
+            }
+            Rule::Julian0(n) => i64::from(n),
+            Rule::MonthWeekDay(m, w, d) => {
+                let first = days_from_civil(year, m, 1).unwrap();
+                // 1970-01-01 is a Thursday, and 0 is Sunday
+                let first_dow = (first + 4).rem_euclid(7);
+                let mut day = (i64::from(d) + 7 - first_dow) % 7 + 7 * (i64::from(w) - 1);
+                let nb_days = i64::from(days_in_month(leap)[m as usize - 1]);
+                while day >= nb_days {
+                    day -= 7;
+                }
+                first - jan_1 + day
+            }
+        };
+        (jan_1 + day) * 86400 + i64::from(self.time)
+    }
+}
+
+struct Parser<'a>(&'a [u8]);
+impl<'a> Parser<'a> {
+    fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+    fn peek(&self) -> Option<u8> {
+        self.0.first().cloned()
+    }
+    fn eat(&mut self, c: u8) -> bool {
+        if self.peek() == Some(c) {
+            self.0 = &self.0[1..];
+            true
+        } else {
+            false
+        }
+    }
+    fn take_while(&mut self, f: impl Fn(u8) -> bool) -> &'a [u8] {
+        let len = self.0.iter().take_while(|&&c| f(c)).count();
+        let (res, rest) = self.0.split_at(len);
+        self.0 = rest;
+        res
+    }
+    fn name(&mut self) -> Result<String<U8>, ParseError> {
+        let name = if self.eat(b'<') {
+            let name = self.take_while(|c| c.is_ascii_alphanumeric() || c == b'+' || c == b'-');
+            if !self.eat(b'>') {
+                return Err(ParseError::InvalidName);
+            }
+            name
+        } else {
+            self.take_while(|c| c.is_ascii_alphabetic())
+        };
+        if name.len() < 3 {
+            return Err(ParseError::InvalidName);
+        }
+        let mut res = String::new();
+        for &c in name {
+            res.push(c as char).map_err(|_| ParseError::InvalidName)?;
+        }
+        Ok(res)
+    }
+    fn number(&mut self, max: u32) -> Option<u32> {
+        let digits = self.take_while(|c| c.is_ascii_digit());
+        if digits.is_empty() || digits.len() > 3 {
+            return None;
+        }
+        let n = digits
+            .iter()
+            .fold(0, |acc, &c| acc * 10 + u32::from(c - b'0'));
+        if n > max {
+            return None;
+        }
+        Some(n)
+    }
+    /// `[+-]hh[:mm[:ss]]`, in seconds, with the sign of the TZ string
+    fn time(&mut self, max_hour: u32) -> Option<i32> {
+        let sign = if self.eat(b'-') {
+            -1
+        } else {
+            self.eat(b'+');
+            1
+        };
+        let mut secs = self.number(max_hour)? * 3600;
+        if self.eat(b':') {
+            secs += self.number(59)? * 60;
+            if self.eat(b':') {
+                secs += self.number(59)?;
+            }
+        }
+        Some(sign * secs as i32)
+    }
+    fn offset(&mut self) -> Option<i32> {
+        self.time(24)
+    }
+    fn transition(&mut self) -> Option<Transition> {
+        if !self.eat(b',') {
+            return None;
+        }
+        let rule = if self.eat(b'J') {
+            match self.number(365)? {
+                0 => return None,
+                n => Rule::Julian1(n as u16),
+            }
+        } else if self.eat(b'M') {
+            let m = self.number(12)?;
+            if m == 0 || !self.eat(b'.') {
+                return None;
+            }
+            let w = self.number(5)?;
+            if w == 0 || !self.eat(b'.') {
+                return None;
+            }
+            let d = self.number(6)?;
+            Rule::MonthWeekDay(m as u8, w as u8, d as u8)
+        } else {
+            Rule::Julian0(self.number(365)? as u16)
+        };
+        let time = if self.eat(b'/') {
+            self.time(167)?
+        } else {
+            2 * 3600
+        };
+        Some(Transition { rule, time })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    const PARIS: &str = "CET-1CEST,M3.5.0,M10.5.0/3";
+
+    #[test]
+    fn test_parse() {
+        let tz = TimeZone::parse(PARIS).unwrap();
+        assert_eq!(tz.std_name, "CET");
+        assert_eq!(tz.std_offset, 3600);
+        let dst = tz.dst.unwrap();
+        assert_eq!(dst.name, "CEST");
+        assert_eq!(dst.offset, 7200);
+        assert_eq!(dst.start.rule, Rule::MonthWeekDay(3, 5, 0));
+        assert_eq!(dst.start.time, 7200);
+        assert_eq!(dst.end.rule, Rule::MonthWeekDay(10, 5, 0));
+        assert_eq!(dst.end.time, 3 * 3600);
+
+        let tz = TimeZone::parse("<+0330>-3:30").unwrap();
+        assert_eq!(tz.std_name, "+0330");
+        assert_eq!(tz.std_offset, 3 * 3600 + 30 * 60);
+        assert!(tz.dst.is_none());
+
+        let tz = TimeZone::parse("EST5EDT").unwrap();
+        assert_eq!(tz.std_offset, -5 * 3600);
+        assert_eq!(tz.dst.unwrap().offset, -4 * 3600);
+
+        let tz = TimeZone::parse("<-03>3<-02>,M3.5.0/-2,M10.5.0/-1").unwrap();
+        assert_eq!(tz.dst.unwrap().start.time, -2 * 3600);
+
+        assert_eq!(TimeZone::parse(""), Err(ParseError::InvalidName));
+        assert_eq!(TimeZone::parse("UT0"), Err(ParseError::InvalidName));
+        assert_eq!(TimeZone::parse("UTC"), Err(ParseError::InvalidOffset));
+        assert_eq!(TimeZone::parse("UTC0x"), Err(ParseError::InvalidName));
+        assert_eq!(
+            TimeZone::parse("CET-1CEST,M3.5.0"),
+            Err(ParseError::InvalidRule)
+        );
+        assert_eq!(
+            TimeZone::parse("CET-1CEST,M13.5.0,M10.5.0/3"),
+            Err(ParseError::InvalidRule)
+        );
+        assert_eq!(
+            TimeZone::parse("CET-1CEST,M3.5.0,M10.5.0/3x"),
+            Err(ParseError::TrailingCharacters)
+        );
+    }
+
+    #[test]
+    fn test_paris() {
+        let tz = TimeZone::parse(PARIS).unwrap();
+
+        // 2018-03-25 00:59:59 UTC, just before DST
+        assert_eq!(tz.to_local(1521939599).unwrap().hour(), 1);
+        assert_eq!(tz.name(1521939599), "CET");
+        // 2018-03-25 01:00:00 UTC, DST
+        assert_eq!(tz.to_local(1521939600).unwrap().hour(), 3);
+        assert_eq!(tz.name(1521939600), "CEST");
+        // 2018-10-28 00:59:59 UTC, still DST
+        assert_eq!(tz.to_local(1540688399).unwrap().hour(), 2);
+        assert_eq!(tz.to_local(1540688399).unwrap().minute(), 59);
+        // 2018-10-28 01:00:00 UTC, back to standard time
+        assert_eq!(tz.to_local(1540688400).unwrap().hour(), 2);
+        assert_eq!(tz.to_local(1540688400).unwrap().minute(), 0);
+        assert!(!tz.is_dst(1540688400));
+
+        let local = tz.to_local(1540052501).unwrap();
+        assert_eq!(local.hour(), 18);
+        assert_eq!(tz.to_utc(&local), 1540052501);
+
+        // ambiguous: first occurrence
+        assert_eq!(tz.to_utc(&DateTime::new(1540693800)), 1540686600);
+        // non existent: interpreted in standard time
+        assert_eq!(tz.to_utc(&DateTime::new(1521945000)), 1521941400);
+        // but reached at the change to DST, 03:00 CEST
+        assert_eq!(tz.reached_at(&DateTime::new(1521945000)), 1521939600);
+        assert_eq!(tz.reached_at(&DateTime::new(1521946800)), 1521939600);
+        assert_eq!(tz.reached_at(&DateTime::new(1521943200)), 1521939600);
+        assert_eq!(tz.reached_at(&DateTime::new(1521943140)), 1521939540);
+        assert_eq!(tz.reached_at(&DateTime::new(1540693800)), 1540686600);
+        assert_eq!(tz.reached_at(&local), 1540052501);
+    }
+
+    #[test]
+    fn test_out_of_the_rtc_range() {
+        let tz = TimeZone::parse(PARIS).unwrap();
+        // 1969-07-20 20:17:40 UTC, before the DST rules of France
+        let moon_landing = DateTime::from_timestamp(-14182940).unwrap();
+        let local = tz.to_local(moon_landing.timestamp()).unwrap();
+        assert_eq!((local.hour(), tz.name(-14182940)), (22, "CEST"));
+        assert_eq!(tz.to_utc(&local), -14182940);
+        // 2200-01-01 00:00:00 UTC
+        let local = tz.to_local(7258118400).unwrap();
+        assert_eq!((local.year(), local.hour()), (2200, 1));
+        assert_eq!(tz.to_utc(&local), 7258118400);
+        // out of the years of `DateTime`
+        assert_eq!(tz.to_local(-62167219200).unwrap().hour(), 1);
+        assert_eq!(tz.to_local(2005949145599), None);
+        assert!(!tz.is_dst(core::i64::MIN));
+        assert_eq!(tz.to_local(core::i64::MAX), None);
+    }
+
+    #[test]
+    fn test_southern_hemisphere() {
+        let tz = TimeZone::parse("AEST-10AEDT,M10.1.0,M4.1.0/3").unwrap();
+        // 2019-01-01 00:00:00 UTC
+        assert!(tz.is_dst(1546300800));
+        assert_eq!(tz.to_local(1546300800).unwrap().hour(), 11);
+        // 2019-07-01 00:00:00 UTC
+        assert!(!tz.is_dst(1561939200));
+        assert_eq!(tz.to_local(1561939200).unwrap().hour(), 10);
+    }
+
+    #[test]
+    fn test_julian() {
+        let j1 = Transition {
+            rule: Rule::Julian1(60),
+            time: 0,
+        };
+        let j0 = Transition {
+            rule: Rule::Julian0(59),
+            time: 0,
+        };
+        // 2020 is a leap year: J60 is March 1st, 59 is February 29th
+        assert_eq!(DateTime::new(j1.local_epoch(2020) as u32).month(), 3);
+        assert_eq!(DateTime::new(j1.local_epoch(2020) as u32).day(), 1);
+        assert_eq!(DateTime::new(j0.local_epoch(2020) as u32).month(), 2);
+        assert_eq!(DateTime::new(j0.local_epoch(2020) as u32).day(), 29);
+        assert_eq!(DateTime::new(j0.local_epoch(2019) as u32).month(), 3);
+    }
+}
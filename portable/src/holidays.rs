@@ -0,0 +1,289 @@
+//! Public holidays, used to skip the alarms.
+
+use crate::datetime::{Date, DayOfWeek};
+use crate::locale::Locale;
+
+/// How the date of a holiday is computed each year.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Rule {
+    /// The same day every year.
+    Fixed { month: u8, day: u8 },
+    /// The same day every year, observed the Friday before when it is a
+    /// Saturday and the Monday after when it is a Sunday.
+    Observed { month: u8, day: u8 },
+    /// The `nth` day of the week of the month, `-1` being the last one.
+    NthWeekday { month: u8, nth: i8, dow: DayOfWeek },
+    /// A number of days after Easter Sunday, negative before.
+    Easter(i16),
+}
+impl Rule {
+    /// The date of the holiday in `year`, which may be in the previous
+    /// year for the `Observed` rules.
+    pub fn date(self, year: u16) -> Option<Date> {
+        use self::Rule::*;
+        match self {
+            Fixed { month, day } => Date::new(year, month, day).ok(),
+            Observed { month, day } => {
+                let date = Date::new(year, month, day).ok()?;
+                let shift = match date.day_of_week() {
+                    DayOfWeek::Saturday => -1,
+                    DayOfWeek::Sunday => 1,
+                    _ => 0,
+                };
+                Date::from_days(date.days() + shift)
+            }
+            NthWeekday { month, nth, dow } => {
+                let first = Date::new(year, month, 1).ok()?;
+                let first_dow = (dow as i64 - first.day_of_week() as i64).rem_euclid(7);
+                let day = if nth > 0 {
+                    first_dow + 7 * (i64::from(nth) - 1)
+                } else {
+                    let nb_days = i64::from(first.days_in_month());
+                    let last_dow = first_dow + (nb_days - 1 - first_dow) / 7 * 7;
+                    last_dow + 7 * (i64::from(nth) + 1)
+                };
+                Date::from_days(first.days() + day).filter(|d| d.month() == month)
+            }
+            Easter(days) => Date::from_days(easter(year).days() + i64::from(days)),
+        }
+    }
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct Holiday {
+    /// The name in the language of the country.
+    pub name: &'static str,
+    pub rule: Rule,
+}
+
+/// The sets of public holidays, only the national ones.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Holidays {
+    None,
+    France,
+    Germany,
+    Spain,
+    UnitedStates,
+}
+impl Default for Holidays {
+    /// France, as the default time zone.
+    fn default() -> Self {
+        Holidays::France
+    }
+}
+impl Holidays {
+    pub fn next(self) -> Self {
+        use self::Holidays::*;
+        match self {
+            None => France,
+            France => Germany,
+            Germany => Spain,
+            Spain => UnitedStates,
+            UnitedStates => None,
+        }
+    }
+    pub fn prev(self) -> Self {
+        use self::Holidays::*;
+        match self {
+            None => UnitedStates,
+            France => None,
+            Germany => France,
+            Spain => Germany,
+            UnitedStates => Spain,
+        }
+    }
+    pub fn as_u8(self) -> u8 {
+        self as u8
+    }
+    pub fn try_from(u: u8) -> Option<Self> {
+        use self::Holidays::*;
+        [None, France, Germany, Spain, UnitedStates]
+            .get(u as usize)
+            .cloned()
+    }
+    /// The name of the country.
+    pub fn name(self, locale: Locale) -> &'static str {
+        locale.strings().countries[self as usize]
+    }
+    pub fn holidays(self) -> &'static [Holiday] {
+        use self::Holidays::*;
+        match self {
+            None => &[],
+            France => &FRANCE,
+            Germany => &GERMANY,
+            Spain => &SPAIN,
+            UnitedStates => &UNITED_STATES,
+        }
+    }
+    /// The holiday falling on `date`, if any.
+    pub fn get(self, date: &Date) -> Option<&'static Holiday> {
+        // an observed holiday may be the last day of the previous year
+        let year = date.year();
+        self.holidays().iter().find(|h| {
+            h.rule.date(year) == Some(*date) || year < 65535 && h.rule.date(year + 1) == Some(*date)
+        })
+    }
+    pub fn contains(self, date: &Date) -> bool {
+        self.get(date).is_some()
+    }
+}
+
+/// Easter Sunday of the Gregorian calendar, with the anonymous
+/// algorithm published in Nature in 1876.
+pub fn easter(year: u16) -> Date {
+    let year = u32::from(year);
+    let a = year % 19;
+    let (b, c) = (year / 100, year % 100);
+    let (d, e) = (b / 4, b % 4);
+    let f = (b + 8) / 25;
+    let g = (b - f + 1) / 3;
+    let h = (19 * a + b - d - g + 15) % 30;
+    let (i, k) = (c / 4, c % 4);
+    let l = (32 + 2 * e + 2 * i - h - k) % 7;
+    let m = (a + 11 * h + 22 * l) / 451;
+    let month = (h + l - 7 * m + 114) / 31;
+    let day = (h + l - 7 * m + 114) % 31 + 1;
+    Date::new(year as u16, month as u8, day as u8).unwrap()
+}
+
+const fn fixed(name: &'static str, month: u8, day: u8) -> Holiday {
+    Holiday {
+        name,
+        rule: Rule::Fixed { month, day },
+    }
+}
+const fn observed(name: &'static str, month: u8, day: u8) -> Holiday {
+    Holiday {
+        name,
+        rule: Rule::Observed { month, day },
+    }
+}
+const fn nth(name: &'static str, month: u8, nth: i8, dow: DayOfWeek) -> Holiday {
+    Holiday {
+        name,
+        rule: Rule::NthWeekday { month, nth, dow },
+    }
+}
+const fn easter_day(name: &'static str, days: i16) -> Holiday {
+    Holiday {
+        name,
+        rule: Rule::Easter(days),
+    }
+}
+
+static FRANCE: [Holiday; 11] = [
+    fixed("Jour de l'an", 1, 1),
+    easter_day("Lundi de Pâques", 1),
+    fixed("Fête du Travail", 5, 1),
+    fixed("Victoire 1945", 5, 8),
+    easter_day("Ascension", 39),
+    easter_day("Lundi de Pentecôte", 50),
+    fixed("Fête nationale", 7, 14),
+    fixed("Assomption", 8, 15),
+    fixed("Toussaint", 11, 1),
+    fixed("Armistice 1918", 11, 11),
+    fixed("Noël", 12, 25),
+];
+
+static GERMANY: [Holiday; 9] = [
+    fixed("Neujahr", 1, 1),
+    easter_day("Karfreitag", -2),
+    easter_day("Ostermontag", 1),
+    fixed("Tag der Arbeit", 5, 1),
+    easter_day("Christi Himmelfahrt", 39),
+    easter_day("Pfingstmontag", 50),
+    fixed("Tag der Deutschen Einheit", 10, 3),
+    fixed("Erster Weihnachtstag", 12, 25),
+    fixed("Zweiter Weihnachtstag", 12, 26),
+];
+
+static SPAIN: [Holiday; 10] = [
+    fixed("Año Nuevo", 1, 1),
+    fixed("Epifanía del Señor", 1, 6),
+    easter_day("Viernes Santo", -2),
+    fixed("Fiesta del Trabajo", 5, 1),
+    fixed("Asunción de la Virgen", 8, 15),
+    fixed("Fiesta Nacional de España", 10, 12),
+    fixed("Todos los Santos", 11, 1),
+    fixed("Día de la Constitución", 12, 6),
+    fixed("Inmaculada Concepción", 12, 8),
+    fixed("Navidad", 12, 25),
+];
+
+static UNITED_STATES: [Holiday; 11] = [
+    observed("New Year's Day", 1, 1),
+    nth("Martin Luther King Jr. Day", 1, 3, DayOfWeek::Monday),
+    nth("Washington's Birthday", 2, 3, DayOfWeek::Monday),
+    nth("Memorial Day", 5, -1, DayOfWeek::Monday),
+    observed("Juneteenth", 6, 19),
+    observed("Independence Day", 7, 4),
+    nth("Labor Day", 9, 1, DayOfWeek::Monday),
+    nth("Columbus Day", 10, 2, DayOfWeek::Monday),
+    observed("Veterans Day", 11, 11),
+    nth("Thanksgiving Day", 11, 4, DayOfWeek::Thursday),
+    observed("Christmas Day", 12, 25),
+];
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn date(year: u16, month: u8, day: u8) -> Date {
+        Date::new(year, month, day).unwrap()
+    }
+
+    #[test]
+    fn test_easter() {
+        assert_eq!(easter(2019), date(2019, 4, 21));
+        assert_eq!(easter(2020), date(2020, 4, 12));
+        assert_eq!(easter(2038), date(2038, 4, 25));
+        assert_eq!(easter(2285), date(2285, 3, 22));
+        assert_eq!(easter(1961), date(1961, 4, 2));
+    }
+
+    #[test]
+    fn test_rules() {
+        let rule = |month, nth, dow| Rule::NthWeekday { month, nth, dow };
+        let thanksgiving = rule(11, 4, DayOfWeek::Thursday);
+        assert_eq!(thanksgiving.date(2020), Some(date(2020, 11, 26)));
+        let memorial_day = rule(5, -1, DayOfWeek::Monday);
+        assert_eq!(memorial_day.date(2020), Some(date(2020, 5, 25)));
+        assert_eq!(memorial_day.date(2021), Some(date(2021, 5, 31)));
+        assert_eq!(rule(2, 5, DayOfWeek::Monday).date(2021), None);
+        assert_eq!(
+            rule(3, 5, DayOfWeek::Monday).date(2021),
+            Some(date(2021, 3, 29))
+        );
+
+        let new_year = Rule::Observed { month: 1, day: 1 };
+        assert_eq!(new_year.date(2022), Some(date(2021, 12, 31)));
+        assert_eq!(new_year.date(2023), Some(date(2023, 1, 2)));
+        assert_eq!(Rule::Easter(39).date(2020), Some(date(2020, 5, 21)));
+        assert_eq!(Rule::Fixed { month: 2, day: 29 }.date(2021), None);
+    }
+
+    #[test]
+    fn test_holidays() {
+        let france = Holidays::France;
+        assert_eq!(
+            france.get(&date(2020, 7, 14)).unwrap().name,
+            "Fête nationale"
+        );
+        assert!(france.contains(&date(2020, 6, 1)));
+        assert!(!france.contains(&date(2020, 6, 2)));
+        assert!(Holidays::Germany.contains(&date(2020, 4, 10)));
+        assert!(!france.contains(&date(2020, 4, 10)));
+        assert!(Holidays::UnitedStates.contains(&date(2021, 12, 31)));
+        assert!(Holidays::UnitedStates.contains(&date(2021, 7, 5)));
+        assert!(!Holidays::None.contains(&date(2020, 1, 1)));
+
+        let mut holidays = Holidays::None;
+        for _ in 0..5 {
+            assert_eq!(Holidays::try_from(holidays.as_u8()), Some(holidays));
+            assert_eq!(holidays.next().prev(), holidays);
+            holidays = holidays.next();
+        }
+        assert_eq!(holidays, Holidays::None);
+        assert_eq!(Holidays::try_from(5), None);
+    }
+}
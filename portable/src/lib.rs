@@ -5,5 +5,14 @@ extern crate std;
 
 pub mod alarm;
 pub mod button;
+pub mod calendars;
+pub mod calibration;
 pub mod datetime;
+pub mod holidays;
+pub mod locale;
+pub mod moon;
+pub mod nmea;
+pub mod radio;
+pub mod settings;
+pub mod sun;
 pub mod ui;
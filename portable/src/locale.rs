@@ -0,0 +1,628 @@
+//! Translations of the user interface.
+
+use crate::datetime::DayOfWeek;
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Locale {
+    English,
+    French,
+    German,
+    Spanish,
+}
+impl Default for Locale {
+    fn default() -> Self {
+        Locale::English
+    }
+}
+impl Locale {
+    pub fn next(self) -> Self {
+        use self::Locale::*;
+        match self {
+            English => French,
+            French => German,
+            German => Spanish,
+            Spanish => English,
+        }
+    }
+    pub fn prev(self) -> Self {
+        use self::Locale::*;
+        match self {
+            English => Spanish,
+            French => English,
+            German => French,
+            Spanish => German,
+        }
+    }
+    pub fn as_u8(self) -> u8 {
+        self as u8
+    }
+    pub fn try_from(u: u8) -> Option<Self> {
+        use self::Locale::*;
+        [English, French, German, Spanish].get(u as usize).cloned()
+    }
+    /// The name of the language, in this language.
+    pub fn name(self) -> &'static str {
+        self.strings().language_name
+    }
+    pub fn day_of_week(self, dow: DayOfWeek) -> &'static str {
+        self.strings().days_of_week[dow as usize]
+    }
+    /// Two letter abbreviation of the day of the week.
+    pub fn day_of_week_short(self, dow: DayOfWeek) -> &'static str {
+        self.strings().days_of_week_short[dow as usize]
+    }
+    /// `month` is in `1..=12`.
+    pub fn month(self, month: u8) -> &'static str {
+        self.strings().months[month as usize - 1]
+    }
+    /// Three letter abbreviation of the month, `month` is in `1..=12`.
+    pub fn month_short(self, month: u8) -> &'static str {
+        self.strings().months_short[month as usize - 1]
+    }
+    pub fn strings(self) -> &'static Strings {
+        use self::Locale::*;
+        match self {
+            English => &ENGLISH,
+            French => &FRENCH,
+            German => &GERMAN,
+            Spanish => &SPANISH,
+        }
+    }
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum TimeFormat {
+    TwentyFourHour,
+    TwelveHour,
+}
+impl Default for TimeFormat {
+    fn default() -> Self {
+        TimeFormat::TwentyFourHour
+    }
+}
+impl TimeFormat {
+    pub fn toggle(self) -> Self {
+        use self::TimeFormat::*;
+        match self {
+            TwentyFourHour => TwelveHour,
+            TwelveHour => TwentyFourHour,
+        }
+    }
+    pub fn as_u8(self) -> u8 {
+        self as u8
+    }
+    pub fn try_from(u: u8) -> Option<Self> {
+        use self::TimeFormat::*;
+        [TwentyFourHour, TwelveHour].get(u as usize).cloned()
+    }
+    /// Returns the hour to display and its suffix: `" AM"`, `" PM"`, or
+    /// nothing in 24-hour format. Midnight is 12 AM and noon 12 PM.
+    pub fn hour(self, hour: u8) -> (u8, &'static str) {
+        match self {
+            TimeFormat::TwentyFourHour => (hour, ""),
+            TimeFormat::TwelveHour => {
+                let suffix = if hour < 12 { " AM" } else { " PM" };
+                ((hour + 11) % 12 + 1, suffix)
+            }
+        }
+    }
+}
+
+/// The translated labels of the user interface.
+pub struct Strings {
+    pub language_name: &'static str,
+    pub days_of_week: [&'static str; 7],
+    pub days_of_week_short: [&'static str; 7],
+    pub months: [&'static str; 12],
+    pub months_short: [&'static str; 12],
+    pub menu: &'static str,
+    pub main_screen: &'static str,
+    pub set_clock: &'static str,
+    pub manage_alarms: &'static str,
+    pub language: &'static str,
+    pub use_12_hour: &'static str,
+    pub use_24_hour: &'static str,
+    pub set_location: &'static str,
+    pub set_latitude: &'static str,
+    pub set_longitude: &'static str,
+    pub calendar: &'static str,
+    /// Names of the secondary calendars, the first one being none.
+    pub calendars: [&'static str; 4],
+    pub holidays: &'static str,
+    /// Names of the countries of the public holidays, the first one
+    /// being none.
+    pub countries: [&'static str; 5],
+    /// The grace period of the late alarms.
+    pub grace_period: &'static str,
+    pub missed_alarm: &'static str,
+    /// The settings or the alarms, after a failure of the flash.
+    pub not_saved: &'static str,
+    /// The vacation suspending the alarms.
+    pub vacation: &'static str,
+    pub vacation_until: &'static str,
+    pub no_vacation: &'static str,
+    /// Label of the sunrise and sunset times.
+    pub sun: &'static str,
+    pub edit: &'static str,
+    pub set_year: &'static str,
+    pub set_month: &'static str,
+    pub set_day: &'static str,
+    pub set_hour: &'static str,
+    pub set_minute: &'static str,
+    pub select_alarm: &'static str,
+    pub new_alarm: &'static str,
+    pub alarm: &'static str,
+    pub no_alarm: &'static str,
+    pub on: &'static str,
+    pub off: &'static str,
+    pub one_time: &'static str,
+    pub never: &'static str,
+    pub enable: &'static str,
+    pub disable: &'static str,
+    pub set_time: &'static str,
+    pub set_label: &'static str,
+    pub label: &'static str,
+    pub repeat: &'static str,
+    pub set_one_time: &'static str,
+    pub manage_repeat: &'static str,
+    /// The recurrences of the alarms.
+    pub set_recurrence: &'static str,
+    pub weekly: &'static str,
+    pub on_date: &'static str,
+    pub monthly: &'static str,
+    pub yearly: &'static str,
+    /// The shift cycles.
+    pub cycle: &'static str,
+    pub cycle_days: &'static str,
+    pub day: &'static str,
+    pub second_time: &'static str,
+    pub skip_holidays: &'static str,
+    pub ring_on_holidays: &'static str,
+    /// The skip of the next ring of an alarm.
+    pub skip_next: &'static str,
+    pub ring_next: &'static str,
+    pub skipped: &'static str,
+    pub set_snooze: &'static str,
+    pub snooze_duration: &'static str,
+    pub max_snoozes: &'static str,
+    /// The hints of the buttons while an alarm rings.
+    pub snooze_hint: &'static str,
+    pub stop_hint: &'static str,
+    pub remove_alarm: &'static str,
+    pub save_and_quit: &'static str,
+    /// Prefix and suffix of the day in the menu entry adding it to the
+    /// repetitions.
+    pub add_day: (&'static str, &'static str),
+    /// Prefix and suffix of the day in the menu entry removing it from
+    /// the repetitions.
+    pub remove_day: (&'static str, &'static str),
+    pub back: &'static str,
+}
+
+static ENGLISH: Strings = Strings {
+    language_name: "English",
+    days_of_week: [
+        "Monday",
+        "Tuesday",
+        "Wednesday",
+        "Thursday",
+        "Friday",
+        "Saturday",
+        "Sunday",
+    ],
+    days_of_week_short: ["Mo", "Tu", "We", "Th", "Fr", "Sa", "Su"],
+    months: [
+        "January",
+        "February",
+        "March",
+        "April",
+        "May",
+        "June",
+        "July",
+        "August",
+        "September",
+        "October",
+        "November",
+        "December",
+    ],
+    months_short: [
+        "Jan", "Feb", "Mar", "Apr", "May", "Jun", "Jul", "Aug", "Sep", "Oct", "Nov", "Dec",
+    ],
+    menu: "Menu:",
+    main_screen: "Main screen",
+    set_clock: "Set clock",
+    manage_alarms: "Manage alarms",
+    language: "Language",
+    use_12_hour: "12-hour clock",
+    use_24_hour: "24-hour clock",
+    set_location: "Set location",
+    set_latitude: "Set latitude",
+    set_longitude: "Set longitude",
+    calendar: "Secondary calendar",
+    calendars: ["None", "Hijri", "Hebrew", "Chinese"],
+    holidays: "Public holidays",
+    countries: ["None", "France", "Germany", "Spain", "United States"],
+    grace_period: "Alarm grace period",
+    missed_alarm: "Missed alarm",
+    not_saved: "Not saved",
+    vacation: "Vacation",
+    vacation_until: "Vacation to",
+    no_vacation: "No vacation",
+    sun: "Sun",
+    edit: "Edit:",
+    set_year: "Set year",
+    set_month: "Set month",
+    set_day: "Set day",
+    set_hour: "Set hour",
+    set_minute: "Set minute",
+    select_alarm: "Select alarm:",
+    new_alarm: "New alarm",
+    alarm: "Alarm:",
+    no_alarm: "No alarm",
+    on: "On",
+    off: "Off",
+    one_time: "one time",
+    never: "never",
+    enable: "Enable",
+    disable: "Disable",
+    set_time: "Set Time",
+    set_label: "Set label",
+    label: "Label:",
+    repeat: "Repeat",
+    set_one_time: "One time",
+    manage_repeat: "Manage repeat",
+    set_recurrence: "Recurrence",
+    weekly: "Weekly",
+    on_date: "On a date",
+    monthly: "Monthly",
+    yearly: "Yearly",
+    cycle: "Cycle",
+    cycle_days: "Days in the cycle:",
+    day: "Day",
+    second_time: "Second time:",
+    skip_holidays: "Skip public holidays",
+    ring_on_holidays: "Ring on public holidays",
+    skip_next: "Skip the next ring",
+    ring_next: "Ring next time",
+    skipped: "Skipped",
+    set_snooze: "Set snooze",
+    snooze_duration: "Snooze duration:",
+    max_snoozes: "Maximum snoozes:",
+    snooze_hint: "OK: snooze",
+    stop_hint: "Cancel: stop",
+    remove_alarm: "Remove alarm",
+    save_and_quit: "Save and quit",
+    add_day: ("Add ", ""),
+    remove_day: ("Remove ", ""),
+    back: "Back",
+};
+
+static FRENCH: Strings = Strings {
+    language_name: "Français",
+    days_of_week: [
+        "lundi", "mardi", "mercredi", "jeudi", "vendredi", "samedi", "dimanche",
+    ],
+    days_of_week_short: ["Lu", "Ma", "Me", "Je", "Ve", "Sa", "Di"],
+    months: [
+        "janvier",
+        "février",
+        "mars",
+        "avril",
+        "mai",
+        "juin",
+        "juillet",
+        "août",
+        "septembre",
+        "octobre",
+        "novembre",
+        "décembre",
+    ],
+    months_short: [
+        "jan", "fév", "mar", "avr", "mai", "jun", "jul", "aoû", "sep", "oct", "nov", "déc",
+    ],
+    menu: "Menu :",
+    main_screen: "Écran principal",
+    set_clock: "Régler l'heure",
+    manage_alarms: "Gérer les réveils",
+    language: "Langue",
+    use_12_hour: "Format 12 heures",
+    use_24_hour: "Format 24 heures",
+    set_location: "Régler la position",
+    set_latitude: "Régler la latitude",
+    set_longitude: "Régler la longitude",
+    calendar: "Second calendrier",
+    calendars: ["Aucun", "Hégire", "Hébraïque", "Chinois"],
+    holidays: "Jours fériés",
+    countries: ["Aucun", "France", "Allemagne", "Espagne", "États-Unis"],
+    grace_period: "Délai de rattrapage",
+    missed_alarm: "Réveil manqué",
+    not_saved: "Non enregistré",
+    vacation: "Vacances",
+    vacation_until: "Vacances au",
+    no_vacation: "Pas de vacances",
+    sun: "Soleil",
+    edit: "Modifier :",
+    set_year: "Régler l'année",
+    set_month: "Régler le mois",
+    set_day: "Régler le jour",
+    set_hour: "Régler l'heure",
+    set_minute: "Régler la minute",
+    select_alarm: "Choisir un réveil :",
+    new_alarm: "Nouveau réveil",
+    alarm: "Réveil :",
+    no_alarm: "Pas de réveil",
+    on: "Oui",
+    off: "Non",
+    one_time: "une fois",
+    never: "jamais",
+    enable: "Activer",
+    disable: "Désactiver",
+    set_time: "Régler l'heure",
+    set_label: "Changer le libellé",
+    label: "Libellé :",
+    repeat: "Répéter",
+    set_one_time: "Une seule fois",
+    manage_repeat: "Gérer la répétition",
+    set_recurrence: "Récurrence",
+    weekly: "Hebdomadaire",
+    on_date: "À une date",
+    monthly: "Mensuel",
+    yearly: "Annuel",
+    cycle: "Cycle",
+    cycle_days: "Jours du cycle :",
+    day: "Jour",
+    second_time: "Second horaire :",
+    skip_holidays: "Sauter les jours fériés",
+    ring_on_holidays: "Sonner les jours fériés",
+    skip_next: "Sauter la prochaine sonnerie",
+    ring_next: "Sonner la prochaine fois",
+    skipped: "Sautée",
+    set_snooze: "Régler la répétition",
+    snooze_duration: "Durée de répétition :",
+    max_snoozes: "Répétitions maximum :",
+    snooze_hint: "OK : répéter",
+    stop_hint: "Annuler : arrêter",
+    remove_alarm: "Supprimer le réveil",
+    save_and_quit: "Enregistrer et quitter",
+    add_day: ("Ajouter ", ""),
+    remove_day: ("Retirer ", ""),
+    back: "Retour",
+};
+
+static GERMAN: Strings = Strings {
+    language_name: "Deutsch",
+    days_of_week: [
+        "Montag",
+        "Dienstag",
+        "Mittwoch",
+        "Donnerstag",
+        "Freitag",
+        "Samstag",
+        "Sonntag",
+    ],
+    days_of_week_short: ["Mo", "Di", "Mi", "Do", "Fr", "Sa", "So"],
+    months: [
+        "Januar",
+        "Februar",
+        "März",
+        "April",
+        "Mai",
+        "Juni",
+        "Juli",
+        "August",
+        "September",
+        "Oktober",
+        "November",
+        "Dezember",
+    ],
+    months_short: [
+        "Jan", "Feb", "Mär", "Apr", "Mai", "Jun", "Jul", "Aug", "Sep", "Okt", "Nov", "Dez",
+    ],
+    menu: "Menü:",
+    main_screen: "Hauptanzeige",
+    set_clock: "Uhr stellen",
+    manage_alarms: "Wecker verwalten",
+    language: "Sprache",
+    use_12_hour: "12-Stunden-Format",
+    use_24_hour: "24-Stunden-Format",
+    set_location: "Standort einstellen",
+    set_latitude: "Breite einstellen",
+    set_longitude: "Länge einstellen",
+    calendar: "Zweiter Kalender",
+    calendars: ["Keiner", "Islamisch", "Hebräisch", "Chinesisch"],
+    holidays: "Feiertage",
+    countries: ["Keine", "Frankreich", "Deutschland", "Spanien", "USA"],
+    grace_period: "Nachholfrist",
+    missed_alarm: "Verpasster Wecker",
+    not_saved: "Nicht gespeichert",
+    vacation: "Urlaub",
+    vacation_until: "Urlaub bis",
+    no_vacation: "Kein Urlaub",
+    sun: "Sonne",
+    edit: "Ändern:",
+    set_year: "Jahr einstellen",
+    set_month: "Monat einstellen",
+    set_day: "Tag einstellen",
+    set_hour: "Stunde einstellen",
+    set_minute: "Minute einstellen",
+    select_alarm: "Wecker wählen:",
+    new_alarm: "Neuer Wecker",
+    alarm: "Wecker:",
+    no_alarm: "Kein Wecker",
+    on: "An",
+    off: "Aus",
+    one_time: "einmalig",
+    never: "nie",
+    enable: "Aktivieren",
+    disable: "Deaktivieren",
+    set_time: "Zeit einstellen",
+    set_label: "Name ändern",
+    label: "Name:",
+    repeat: "Wiederholen",
+    set_one_time: "Einmalig",
+    manage_repeat: "Wiederholung",
+    set_recurrence: "Wiederholung",
+    weekly: "Wöchentlich",
+    on_date: "An einem Datum",
+    monthly: "Monatlich",
+    yearly: "Jährlich",
+    cycle: "Zyklus",
+    cycle_days: "Tage im Zyklus:",
+    day: "Tag",
+    second_time: "Zweite Uhrzeit:",
+    skip_holidays: "An Feiertagen aussetzen",
+    ring_on_holidays: "An Feiertagen klingeln",
+    skip_next: "Nächstes Klingeln überspringen",
+    ring_next: "Nächstes Mal klingeln",
+    skipped: "Übersprungen",
+    set_snooze: "Schlummern einstellen",
+    snooze_duration: "Schlummerdauer:",
+    max_snoozes: "Max. Schlummern:",
+    snooze_hint: "OK: Schlummern",
+    stop_hint: "Abbrechen: Stopp",
+    remove_alarm: "Wecker löschen",
+    save_and_quit: "Speichern und beenden",
+    add_day: ("", " hinzufügen"),
+    remove_day: ("", " entfernen"),
+    back: "Zurück",
+};
+
+static SPANISH: Strings = Strings {
+    language_name: "Español",
+    days_of_week: [
+        "lunes",
+        "martes",
+        "miércoles",
+        "jueves",
+        "viernes",
+        "sábado",
+        "domingo",
+    ],
+    days_of_week_short: ["Lu", "Ma", "Mi", "Ju", "Vi", "Sá", "Do"],
+    months: [
+        "enero",
+        "febrero",
+        "marzo",
+        "abril",
+        "mayo",
+        "junio",
+        "julio",
+        "agosto",
+        "septiembre",
+        "octubre",
+        "noviembre",
+        "diciembre",
+    ],
+    months_short: [
+        "ene", "feb", "mar", "abr", "may", "jun", "jul", "ago", "sep", "oct", "nov", "dic",
+    ],
+    menu: "Menú:",
+    main_screen: "Pantalla principal",
+    set_clock: "Ajustar la hora",
+    manage_alarms: "Gestionar alarmas",
+    language: "Idioma",
+    use_12_hour: "Formato de 12 horas",
+    use_24_hour: "Formato de 24 horas",
+    set_location: "Ajustar la ubicación",
+    set_latitude: "Ajustar la latitud",
+    set_longitude: "Ajustar la longitud",
+    calendar: "Segundo calendario",
+    calendars: ["Ninguno", "Hégira", "Hebreo", "Chino"],
+    holidays: "Días festivos",
+    countries: ["Ninguno", "Francia", "Alemania", "España", "Estados Unidos"],
+    grace_period: "Margen de recuperación",
+    missed_alarm: "Alarma perdida",
+    not_saved: "No guardado",
+    vacation: "Vacaciones",
+    vacation_until: "Vacaciones hasta",
+    no_vacation: "Sin vacaciones",
+    sun: "Sol",
+    edit: "Editar:",
+    set_year: "Ajustar el año",
+    set_month: "Ajustar el mes",
+    set_day: "Ajustar el día",
+    set_hour: "Ajustar la hora",
+    set_minute: "Ajustar el minuto",
+    select_alarm: "Elegir alarma:",
+    new_alarm: "Nueva alarma",
+    alarm: "Alarma:",
+    no_alarm: "Sin alarma",
+    on: "Sí",
+    off: "No",
+    one_time: "una vez",
+    never: "nunca",
+    enable: "Activar",
+    disable: "Desactivar",
+    set_time: "Ajustar la hora",
+    set_label: "Cambiar la etiqueta",
+    label: "Etiqueta:",
+    repeat: "Repetir",
+    set_one_time: "Una sola vez",
+    manage_repeat: "Gestionar repetición",
+    set_recurrence: "Recurrencia",
+    weekly: "Semanal",
+    on_date: "En una fecha",
+    monthly: "Mensual",
+    yearly: "Anual",
+    cycle: "Ciclo",
+    cycle_days: "Días del ciclo:",
+    day: "Día",
+    second_time: "Segunda hora:",
+    skip_holidays: "Omitir los festivos",
+    ring_on_holidays: "Sonar los festivos",
+    skip_next: "Omitir la próxima alarma",
+    ring_next: "Sonar la próxima vez",
+    skipped: "Omitida",
+    set_snooze: "Ajustar la repetición",
+    snooze_duration: "Duración de repetición:",
+    max_snoozes: "Repeticiones máximas:",
+    snooze_hint: "OK: posponer",
+    stop_hint: "Cancelar: detener",
+    remove_alarm: "Eliminar la alarma",
+    save_and_quit: "Guardar y salir",
+    add_day: ("Añadir ", ""),
+    remove_day: ("Quitar ", ""),
+    back: "Volver",
+};
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_tables() {
+        for &locale in &[
+            Locale::English,
+            Locale::French,
+            Locale::German,
+            Locale::Spanish,
+        ] {
+            assert_eq!(Locale::try_from(locale.as_u8()), Some(locale));
+            assert_eq!(locale.next().prev(), locale);
+            for &short in &locale.strings().days_of_week_short {
+                assert_eq!(short.chars().count(), 2);
+            }
+            for &short in &locale.strings().months_short {
+                assert_eq!(short.chars().count(), 3);
+            }
+        }
+        assert_eq!(Locale::try_from(4), None);
+        assert_eq!(Locale::French.day_of_week(DayOfWeek::Sunday), "dimanche");
+        assert_eq!(Locale::German.day_of_week_short(DayOfWeek::Tuesday), "Di");
+        assert_eq!(Locale::Spanish.month(12), "diciembre");
+        assert_eq!(Locale::English.month_short(5), "May");
+    }
+
+    #[test]
+    fn test_time_format() {
+        let format = TimeFormat::TwelveHour;
+        assert_eq!(format.hour(0), (12, " AM"));
+        assert_eq!(format.hour(1), (1, " AM"));
+        assert_eq!(format.hour(11), (11, " AM"));
+        assert_eq!(format.hour(12), (12, " PM"));
+        assert_eq!(format.hour(23), (11, " PM"));
+        assert_eq!(format.toggle().hour(0), (0, ""));
+        assert_eq!(TimeFormat::try_from(format.as_u8()), Some(format));
+        assert_eq!(TimeFormat::try_from(0xff), None);
+    }
+}
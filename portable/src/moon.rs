@@ -0,0 +1,224 @@
+//! Moon phases.
+//!
+//! The new and full moons are computed as in the chapter 49 of
+//! Astronomical Algorithms by Jean Meeus, keeping the main periodic
+//! terms: they are accurate to a few minutes around our era. The phase
+//! at a given time is interpolated between two new moons.
+
+use crate::datetime::DateTime;
+use core::f32::consts::PI;
+use core::fmt;
+use libm::{cosf, roundf, sinf};
+
+/// Mean new moon of 2000-01-06, in seconds since 1970-01-01, UTC being
+/// about 64s behind the terrestrial time of the algorithm.
+const FIRST_NEW_MOON: i64 = 947_168_374;
+/// Mean synodic month, in milliseconds.
+const SYNODIC_MONTH: i64 = 2_551_442_878;
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Phase {
+    NewMoon,
+    WaxingCrescent,
+    FirstQuarter,
+    WaxingGibbous,
+    FullMoon,
+    WaningGibbous,
+    LastQuarter,
+    WaningCrescent,
+}
+impl fmt::Display for Phase {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        use self::Phase::*;
+        f.write_str(match self {
+            NewMoon => "New moon",
+            WaxingCrescent => "Waxing crescent",
+            FirstQuarter => "First quarter",
+            WaxingGibbous => "Waxing gibbous",
+            FullMoon => "Full moon",
+            WaningGibbous => "Waning gibbous",
+            LastQuarter => "Last quarter",
+            WaningCrescent => "Waning crescent",
+        })
+    }
+}
+
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct Moon {
+    pub phase: Phase,
+    /// Illuminated part of the disc, in percent.
+    pub illumination: u8,
+    /// Days since the last new moon.
+    pub age: f32,
+    /// Position in the lunation, from 0 at the new moon to 1 at the
+    /// next one, the full moon being around 0.5.
+    pub lunation: f32,
+}
+impl Moon {
+    pub fn new(dt: &DateTime) -> Moon {
+        use self::Phase::*;
+        let t = dt.timestamp();
+        let k = lunation_before(t);
+        let (new_moon, next_new_moon) = (phase_time(k, false), phase_time(k + 1, false));
+        let lunation = (t - new_moon) as f32 / (next_new_moon - new_moon) as f32;
+        let illumination = (1. - cosf(2. * PI * lunation)) / 2.;
+        let phases = [
+            NewMoon,
+            WaxingCrescent,
+            FirstQuarter,
+            WaxingGibbous,
+            FullMoon,
+            WaningGibbous,
+            LastQuarter,
+            WaningCrescent,
+        ];
+        Moon {
+            phase: phases[roundf(lunation * 8.) as usize % 8],
+            illumination: roundf(illumination * 100.) as u8,
+            age: (t - new_moon) as f32 / 86400.,
+            lunation,
+        }
+    }
+}
+
+/// The first new moon strictly after `dt`.
+pub fn next_new_moon(dt: &DateTime) -> Option<DateTime> {
+    next_phase(dt, false)
+}
+/// The first full moon strictly after `dt`.
+pub fn next_full_moon(dt: &DateTime) -> Option<DateTime> {
+    next_phase(dt, true)
+}
+
+fn next_phase(dt: &DateTime, full: bool) -> Option<DateTime> {
+    let t = dt.timestamp();
+    let mut k = lunation_before(t);
+    while phase_time(k, full) <= t {
+        k += 1;
+    }
+    DateTime::from_timestamp(phase_time(k, full))
+}
+
+/// The timestamp of the last new moon strictly before `t`.
+pub(crate) fn new_moon_before(t: i64) -> i64 {
+    phase_time(lunation_before(t - 1), false)
+}
+/// The timestamp of the first new moon strictly after `t`.
+pub(crate) fn new_moon_after(t: i64) -> i64 {
+    phase_time(lunation_before(t) + 1, false)
+}
+
+/// The number of the last new moon before or at `t`, 0 being the one
+/// of 2000-01-06.
+fn lunation_before(t: i64) -> i64 {
+    let mut k = ((t - FIRST_NEW_MOON) * 1000).div_euclid(SYNODIC_MONTH);
+    while phase_time(k, false) > t {
+        k -= 1;
+    }
+    while phase_time(k + 1, false) <= t {
+        k += 1;
+    }
+    k
+}
+
+/// Returns the time of the new moon, or of the following full moon,
+/// of the lunation `k`.
+fn phase_time(k: i64, full: bool) -> i64 {
+    let half = if full { SYNODIC_MONTH / 2 } else { 0 };
+    let mean = FIRST_NEW_MOON + (k * SYNODIC_MONTH + half) / 1000;
+
+    // The angles are computed in integer micro degrees, a `f32` would
+    // lose the precision after a few centuries.
+    let angle = |base: i64, per_lunation: i64| {
+        let micro_degrees = base + per_lunation * k + if full { per_lunation / 2 } else { 0 };
+        (micro_degrees.rem_euclid(360_000_000) as f32 / 1e6).to_radians()
+    };
+    // mean anomalies of the sun and the moon, argument of latitude
+    let m = angle(2_553_400, 29_105_357);
+    let mm = angle(201_564_300, 385_816_935);
+    let f = angle(160_710_800, 390_670_503);
+    // decreasing eccentricity of the Earth's orbit
+    let e = 1. - 0.002_516 * (k as f32 / 1236.85);
+
+    let coefs = if full {
+        [
+            -0.40614, 0.17302, 0.01614, 0.01043, 0.00734, -0.00515, 0.00209,
+        ]
+    } else {
+        [
+            -0.40720, 0.17241, 0.01608, 0.01039, 0.00739, -0.00514, 0.00208,
+        ]
+    };
+    let correction = coefs[0] * sinf(mm)
+        + coefs[1] * e * sinf(m)
+        + coefs[2] * sinf(2. * mm)
+        + coefs[3] * sinf(2. * f)
+        + coefs[4] * e * sinf(mm - m)
+        + coefs[5] * e * sinf(mm + m)
+        + coefs[6] * e * e * sinf(2. * m)
+        - 0.00111 * sinf(mm - 2. * f)
+        - 0.00057 * sinf(mm + 2. * f)
+        + 0.00056 * e * sinf(2. * mm + m);
+    mean + roundf(correction * 86400.) as i64
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::datetime::{Date, Time};
+
+    fn dt(year: u16, month: u8, day: u8, hour: u8, min: u8) -> DateTime {
+        DateTime {
+            date: Date::new(year, month, day).unwrap(),
+            time: Time::new(hour, min, 0).unwrap(),
+        }
+    }
+    fn assert_near(actual: Option<DateTime>, expected: DateTime) {
+        let actual = actual.unwrap();
+        let diff = actual.sub(&expected).as_secs().abs();
+        assert!(diff <= 5 * 60, "{} is not {}", actual, expected);
+    }
+
+    #[test]
+    fn test_phases() {
+        let start = dt(2020, 4, 1, 0, 0);
+        assert_near(next_full_moon(&start), dt(2020, 4, 8, 2, 35));
+        assert_near(next_new_moon(&start), dt(2020, 4, 23, 2, 26));
+        assert_near(next_new_moon(&dt(2000, 1, 1, 0, 0)), dt(2000, 1, 6, 18, 14));
+        assert_near(
+            next_full_moon(&dt(2024, 12, 1, 0, 0)),
+            dt(2024, 12, 15, 9, 2),
+        );
+
+        let full = next_full_moon(&start).unwrap();
+        assert!(next_full_moon(&full).unwrap() > full.add_days(29).unwrap());
+    }
+
+    #[test]
+    fn test_moon() {
+        let full = Moon::new(&dt(2020, 4, 8, 2, 35));
+        assert_eq!(full.phase, Phase::FullMoon);
+        assert_eq!(full.illumination, 100);
+        assert!((full.age - 14.9).abs() < 0.2);
+
+        let new = Moon::new(&dt(2020, 4, 23, 2, 30));
+        assert_eq!(new.phase, Phase::NewMoon);
+        assert_eq!(new.illumination, 0);
+        assert!(new.age < 0.01);
+
+        // first quarter on 2020-04-30 20:38, the interpolation between
+        // the new moons being a bit off
+        let first_quarter = Moon::new(&dt(2020, 4, 30, 20, 38));
+        assert_eq!(first_quarter.phase, Phase::FirstQuarter);
+        assert!((first_quarter.illumination as i32 - 50).abs() <= 5);
+
+        assert_eq!(
+            Moon::new(&dt(2020, 4, 12, 0, 0)).phase,
+            Phase::WaningGibbous
+        );
+        assert_eq!(
+            Moon::new(&dt(2020, 4, 19, 0, 0)).phase,
+            Phase::WaningCrescent
+        );
+    }
+}
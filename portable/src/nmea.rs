@@ -0,0 +1,321 @@
+//! Parser of the NMEA 0183 sentences giving the time, as sent by the GPS
+//! modules: `RMC` (recommended minimum data) and `ZDA` (time and date),
+//! from any talker (`$GPRMC`, `$GNRMC`...).
+
+use crate::datetime::{Date, DateTime, Time};
+use heapless::{consts::*, String};
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum ParseError {
+    /// The sentence does not follow the NMEA 0183 syntax.
+    InvalidFormat,
+    InvalidChecksum,
+    /// Neither a `RMC` nor a `ZDA` sentence.
+    Unsupported,
+    /// The time or the date is not known yet.
+    MissingTime,
+    InvalidDate,
+    InvalidTime,
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct GpsTime {
+    /// In UTC, the fraction of the second being truncated.
+    pub datetime: DateTime,
+    /// Whether the receiver has a fix, the time may come from its own
+    /// clock otherwise. `ZDA` has no status: `parse` returns false and
+    /// `Parser` takes the status of the last `RMC`.
+    pub valid: bool,
+}
+
+/// Parses a sentence, as `$GPZDA,201530.00,04,07,2002,00,00*60`, with
+/// or without the trailing `\r\n`.
+pub fn parse(sentence: &str) -> Result<GpsTime, ParseError> {
+    let sentence = sentence.trim_end_matches(&['\r', '\n'][..]);
+    // the fields are sliced by byte
+    if !sentence.is_ascii() || !sentence.starts_with('$') {
+        return Err(ParseError::InvalidFormat);
+    }
+    let star = sentence.find('*').ok_or(ParseError::InvalidFormat)?;
+    let (data, checksum) = (&sentence[1..star], &sentence[star + 1..]);
+    if checksum.len() != 2 {
+        return Err(ParseError::InvalidFormat);
+    }
+    let checksum = u8::from_str_radix(checksum, 16).map_err(|_| ParseError::InvalidFormat)?;
+    if data.bytes().fold(0, |acc, b| acc ^ b) != checksum {
+        return Err(ParseError::InvalidChecksum);
+    }
+
+    let mut fields = data.split(',');
+    let address = fields.next().unwrap_or("");
+    if address.len() != 5 {
+        return Err(ParseError::InvalidFormat);
+    }
+    match &address[2..] {
+        "RMC" => {
+            // time, status, latitude, N/S, longitude, E/W, speed, course,
+            // date
+            let time = fields.next().ok_or(ParseError::InvalidFormat)?;
+            let status = fields.next().ok_or(ParseError::InvalidFormat)?;
+            let date = fields.nth(6).ok_or(ParseError::InvalidFormat)?;
+            if time.is_empty() || date.len() != 6 {
+                return Err(ParseError::MissingTime);
+            }
+            // GPS started in 1980
+            let year = number(&date[4..6], ParseError::InvalidDate)?;
+            let year = year + if year < 80 { 2000 } else { 1900 };
+            let month = number(&date[2..4], ParseError::InvalidDate)?;
+            let day = number(&date[0..2], ParseError::InvalidDate)?;
+            Ok(GpsTime {
+                datetime: DateTime {
+                    date: Date::new(year, month as u8, day as u8)
+                        .map_err(|_| ParseError::InvalidDate)?,
+                    time: parse_time(time)?,
+                },
+                valid: status == "A",
+            })
+        }
+        "ZDA" => {
+            // time, day, month, year, local zone hours and minutes
+            let time = fields.next().ok_or(ParseError::InvalidFormat)?;
+            let day = fields.next().ok_or(ParseError::InvalidFormat)?;
+            let month = fields.next().ok_or(ParseError::InvalidFormat)?;
+            let year = fields.next().ok_or(ParseError::InvalidFormat)?;
+            if time.is_empty() || day.is_empty() || month.is_empty() || year.is_empty() {
+                return Err(ParseError::MissingTime);
+            }
+            let year = number(year, ParseError::InvalidDate)?;
+            let month = number(month, ParseError::InvalidDate)?;
+            let day = number(day, ParseError::InvalidDate)?;
+            Ok(GpsTime {
+                datetime: DateTime {
+                    date: Date::new(year, month as u8, day as u8)
+                        .map_err(|_| ParseError::InvalidDate)?,
+                    time: parse_time(time)?,
+                },
+                valid: false,
+            })
+        }
+        _ => Err(ParseError::Unsupported),
+    }
+}
+
+fn number(s: &str, err: ParseError) -> Result<u16, ParseError> {
+    if s.is_empty() || !s.bytes().all(|b| b.is_ascii_digit()) {
+        return Err(err);
+    }
+    s.parse().map_err(|_| err)
+}
+/// `hhmmss` followed by an optional fraction of second.
+fn parse_time(s: &str) -> Result<Time, ParseError> {
+    let err = ParseError::InvalidTime;
+    let s = s.split('.').next().unwrap_or("");
+    if s.len() != 6 {
+        return Err(err);
+    }
+    let (hour, min, sec) = (
+        number(&s[0..2], err)?,
+        number(&s[2..4], err)?,
+        number(&s[4..6], err)?,
+    );
+    Time::new(hour as u8, min as u8, sec as u8).map_err(|_| err)
+}
+
+/// Splits the bytes received from a GPS module into sentences and
+/// parses them.
+pub struct Parser {
+    /// A sentence is at most 82 characters long, including `$` and
+    /// `\r\n`.
+    buf: String<U82>,
+    /// The current sentence is too long or not ASCII.
+    invalid: bool,
+    /// The status of the last `RMC`, giving the validity of the `ZDA`.
+    fix: bool,
+}
+impl Default for Parser {
+    fn default() -> Self {
+        Self {
+            buf: String::new(),
+            invalid: true,
+            fix: false,
+        }
+    }
+}
+impl Parser {
+    /// Returns the time when `byte` ends a sentence giving it, the
+    /// other sentences being ignored.
+    pub fn push(&mut self, byte: u8) -> Option<GpsTime> {
+        match byte {
+            b'$' => {
+                self.buf = String::new();
+                self.invalid = false;
+            }
+            b'\n' => {
+                if self.invalid {
+                    return None;
+                }
+                self.invalid = true;
+                let res = parse(&self.buf);
+                if self.buf.get(3..6) == Some("RMC") {
+                    // no fix until a valid `RMC` says otherwise
+                    self.fix = res.map(|time| time.valid) == Ok(true);
+                    return res.ok();
+                }
+                return res.ok().map(|time| GpsTime {
+                    valid: self.fix,
+                    ..time
+                });
+            }
+            _ => {}
+        }
+        if !byte.is_ascii() || self.buf.push(byte as char).is_err() {
+            self.invalid = true;
+        }
+        None
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn datetime(year: u16, month: u8, day: u8, hour: u8, min: u8, sec: u8) -> DateTime {
+        DateTime {
+            date: Date::new(year, month, day).unwrap(),
+            time: Time::new(hour, min, sec).unwrap(),
+        }
+    }
+
+    #[test]
+    fn test_rmc() {
+        let time = parse("$GPRMC,123519,A,4807.038,N,01131.000,E,022.4,084.4,230394,003.1,W*6A");
+        assert_eq!(
+            time,
+            Ok(GpsTime {
+                datetime: datetime(1994, 3, 23, 12, 35, 19),
+                valid: true,
+            })
+        );
+        let time =
+            parse("$GNRMC,083559.00,A,4717.11437,N,00833.91522,E,0.004,77.52,091202,,,A,V*33\r\n");
+        assert_eq!(
+            time.map(|t| t.datetime),
+            Ok(datetime(2002, 12, 9, 8, 35, 59))
+        );
+        // from the clock of the module, before the fix
+        let time = parse("$GPRMC,001225.00,V,,,,,,,150520,,,N*7A");
+        assert_eq!(
+            time,
+            Ok(GpsTime {
+                datetime: datetime(2020, 5, 15, 0, 12, 25),
+                valid: false,
+            })
+        );
+        assert_eq!(
+            parse("$GPRMC,,V,,,,,,,,,,N*53"),
+            Err(ParseError::MissingTime)
+        );
+        assert_eq!(
+            parse("$GPRMC,256119,A,,,,,,,150520,,,A*40"),
+            Err(ParseError::InvalidTime)
+        );
+        assert_eq!(
+            parse("$GPRMC,001225,A,,,,,,,310420,,,A*4B"),
+            Err(ParseError::InvalidDate)
+        );
+    }
+
+    #[test]
+    fn test_zda() {
+        assert_eq!(
+            parse("$GPZDA,201530.00,04,07,2002,00,00*60"),
+            Ok(GpsTime {
+                datetime: datetime(2002, 7, 4, 20, 15, 30),
+                valid: false,
+            })
+        );
+        assert_eq!(parse("$GPZDA,,,,,00,00*48"), Err(ParseError::MissingTime));
+    }
+
+    #[test]
+    fn test_errors() {
+        assert_eq!(
+            parse("$GPZDA,201530.00,04,07,2002,00,00*61"),
+            Err(ParseError::InvalidChecksum)
+        );
+        // a corrupted character
+        assert_eq!(
+            parse("$GPRMC,123519,A,4807.038,N,01131.000,E,022.4,084.4,230395,003.1,W*6A"),
+            Err(ParseError::InvalidChecksum)
+        );
+        assert_eq!(
+            parse("$GPGGA,123519,4807.038,N,01131.000,E,1,08,0.9,545.4,M,46.9,M,,*47"),
+            Err(ParseError::Unsupported)
+        );
+        assert_eq!(
+            parse("GPZDA,201530.00,04,07,2002,00,00*60"),
+            Err(ParseError::InvalidFormat)
+        );
+        assert_eq!(
+            parse("$GPZDA,201530.00,04,07,2002,00,00"),
+            Err(ParseError::InvalidFormat)
+        );
+        assert_eq!(
+            parse("$GPZDA,201530.00,04,07,2002,00,00*6G"),
+            Err(ParseError::InvalidFormat)
+        );
+        // not ASCII, with a valid checksum, would be sliced inside a
+        // character
+        assert_eq!(
+            parse("$G\u{e9}MC,201530.00,04,07,2002,00,00*0B"),
+            Err(ParseError::InvalidFormat)
+        );
+    }
+
+    #[test]
+    fn test_parser() {
+        let mut parser = Parser::default();
+        let stream = b"0,00*60\r\n\
+            $GPGGA,123519,4807.038,N,01131.000,E,1,08,0.9,545.4,M,46.9,M,,*47\r\n\
+            $GPRMC,123519,A,4807.038,N,01131.000,E,022.4,084.4,230394,003.1,W*6B\r\n\
+            $GPZDA,201530.00,04,$GPZDA,201530.00,04,07,2002,00,00*60\r\n\
+            $GPRMC,123519,A,4807.038,N,01131.000,E,022.4,084.4,230394,003.1,W*6A\r\n\
+            $GPZDA,201530.00,04,07,2002,00,00*60\r\n\
+            $GPRMC,001225.00,V,,,,,,,150520,,,N*7A\r\n\
+            $GPZDA,201530.00,04,07,2002,00,00*60\r\n";
+        let times: std::vec::Vec<_> = stream.iter().filter_map(|&b| parser.push(b)).collect();
+        let zda = datetime(2002, 7, 4, 20, 15, 30);
+        assert_eq!(
+            times,
+            [
+                // no `RMC` yet
+                GpsTime {
+                    datetime: zda,
+                    valid: false,
+                },
+                GpsTime {
+                    datetime: datetime(1994, 3, 23, 12, 35, 19),
+                    valid: true,
+                },
+                GpsTime {
+                    datetime: zda,
+                    valid: true,
+                },
+                GpsTime {
+                    datetime: datetime(2020, 5, 15, 0, 12, 25),
+                    valid: false,
+                },
+                GpsTime {
+                    datetime: zda,
+                    valid: false,
+                },
+            ]
+        );
+        // too long
+        let mut parser = Parser::default();
+        let long = [b'$', b'A'];
+        for &b in long.iter().chain(&[b'A'; 100]).chain(b"\r\n") {
+            assert_eq!(parser.push(b), None);
+        }
+    }
+}
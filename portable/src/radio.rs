@@ -0,0 +1,565 @@
+//! Decoder of the time codes broadcast by DCF77 (Germany), MSF (United
+//! Kingdom) and WWVB (United States).
+//!
+//! The receiver modules output a pulse when the carrier is reduced. A
+//! pulse begins each second, its length encoding one symbol, and a
+//! minute frame is delimited by a missing pulse (DCF77) or by longer
+//! pulses (MSF and WWVB). The frames are checked with the parity bits
+//! and the fixed bits, and the confidence grows with the number of
+//! successive frames that agree.
+
+use crate::datetime::{Date, DateTime, Time};
+use core::convert::Infallible;
+use embedded_hal::digital::v2::InputPin;
+use heapless::{consts::*, Vec};
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Protocol {
+    /// 77.5kHz from Mainflingen, in German time.
+    Dcf77,
+    /// 60kHz from Anthorn, in British time.
+    Msf,
+    /// 60kHz from Fort Collins, in UTC.
+    Wwvb,
+}
+
+/// A decoded time.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Decoded {
+    /// The time in UTC at the edge that completed the frame.
+    pub datetime: DateTime,
+    /// The number of successive frames giving consistent times, 1 for a
+    /// frame alone.
+    pub confidence: u8,
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+enum Symbol {
+    /// The A and B bits for MSF, only the first one for the others.
+    Bits(bool, bool),
+    Marker,
+    Invalid,
+}
+
+pub struct Decoder {
+    protocol: Protocol,
+    /// The timestamp of the beginning of the current second.
+    second_start: Option<u32>,
+    /// The beginning of the current pulse, relative to `second_start`.
+    pulse_start: Option<u16>,
+    /// The pulses of the current second, relative to `second_start`.
+    pulses: Vec<(u16, u16), U2>,
+    /// More pulses than expected in the current second.
+    noise: bool,
+    symbols: Vec<Symbol, U64>,
+    /// The last decoded time and its timestamp.
+    last: Option<(DateTime, u32)>,
+    confidence: u8,
+}
+impl Decoder {
+    pub fn new(protocol: Protocol) -> Self {
+        Self {
+            protocol,
+            second_start: None,
+            pulse_start: None,
+            pulses: Vec::new(),
+            noise: false,
+            symbols: Vec::new(),
+            last: None,
+            confidence: 0,
+        }
+    }
+    /// To call on each edge of the receiver output, `reduced` being
+    /// true at the beginning of a pulse. `time` is in milliseconds and
+    /// may wrap.
+    pub fn edge(&mut self, time: u32, reduced: bool) -> Option<Decoded> {
+        if reduced {
+            self.begin_pulse(time)
+        } else {
+            self.end_pulse(time);
+            None
+        }
+    }
+    fn end_pulse(&mut self, time: u32) {
+        if let (Some(second_start), Some(pulse_start)) = (self.second_start, self.pulse_start) {
+            let end = core::cmp::min(time.wrapping_sub(second_start), 2000) as u16;
+            if self.pulses.push((pulse_start, end)).is_err() {
+                self.noise = true;
+            }
+        }
+        self.pulse_start = None;
+    }
+    fn begin_pulse(&mut self, time: u32) -> Option<Decoded> {
+        let elapsed = match self.second_start {
+            None => {
+                self.start_second(time);
+                return None;
+            }
+            Some(second_start) => time.wrapping_sub(second_start),
+        };
+        if elapsed < 900 {
+            // the second pulse of MSF, or some noise
+            self.pulse_start = Some(elapsed as u16);
+            return None;
+        }
+
+        let symbol = self.symbol();
+        self.push(symbol);
+        let nb_seconds = (elapsed + 500) / 1000;
+        if self.protocol == Protocol::Dcf77 && nb_seconds == 2 {
+            // no pulse on the last second of the minute
+            self.push(Symbol::Marker);
+        } else if nb_seconds > 64 {
+            self.symbols = Vec::new();
+        } else {
+            for _ in 1..nb_seconds {
+                self.push(Symbol::Invalid);
+            }
+        }
+        self.start_second(time);
+
+        let (datetime, delay) = self.frame()?;
+        let datetime = datetime.add_seconds(delay)?;
+        let consistent = self.last.as_ref().map_or(false, |&(ref last, last_time)| {
+            let secs = (time.wrapping_sub(last_time) + 500) / 1000;
+            last.add_seconds(i64::from(secs)) == Some(datetime)
+        });
+        self.confidence = if consistent {
+            self.confidence.saturating_add(1)
+        } else {
+            1
+        };
+        self.last = Some((datetime, time));
+        Some(Decoded {
+            datetime,
+            confidence: self.confidence,
+        })
+    }
+    fn start_second(&mut self, time: u32) {
+        self.second_start = Some(time);
+        self.pulse_start = Some(0);
+        self.pulses = Vec::new();
+        self.noise = false;
+    }
+    fn push(&mut self, symbol: Symbol) {
+        if self.symbols.push(symbol).is_err() {
+            // lost synchronization
+            self.symbols = Vec::new();
+            self.symbols.push(symbol).unwrap();
+        }
+    }
+    /// The symbol of the second that just ended, from the lengths of its
+    /// pulses, with about 40ms of tolerance.
+    fn symbol(&self) -> Symbol {
+        use self::Protocol::*;
+        use self::Symbol::*;
+        if self.noise {
+            return Invalid;
+        }
+        match (self.protocol, &self.pulses[..]) {
+            (Dcf77, [(0, len)]) => match len {
+                40..=150 => Bits(false, false),
+                151..=260 => Bits(true, false),
+                _ => Invalid,
+            },
+            (Msf, [(0, len)]) => match len {
+                60..=150 => Bits(false, false),
+                151..=250 => Bits(true, false),
+                251..=360 => Bits(true, true),
+                400..=600 => Marker,
+                _ => Invalid,
+            },
+            (Msf, [(0, 60..=150), (160..=240, 260..=340)]) => Bits(false, true),
+            (Wwvb, [(0, len)]) => match len {
+                100..=350 => Bits(false, false),
+                351..=650 => Bits(true, false),
+                651..=950 => Marker,
+                _ => Invalid,
+            },
+            _ => Invalid,
+        }
+    }
+    /// Decodes the last frame if it just ended, returning the time it
+    /// encodes and the delay in seconds since this time.
+    fn frame(&mut self) -> Option<(DateTime, i64)> {
+        let len = self.symbols.len();
+        let (start, end) = match self.protocol {
+            // the frame is between two markers
+            Protocol::Dcf77 | Protocol::Msf => {
+                if self.symbols.last() != Some(&Symbol::Marker) {
+                    return None;
+                }
+                let start = self.symbols[..len - 1]
+                    .iter()
+                    .rposition(|s| *s == Symbol::Marker)
+                    .map_or(len - 1, |i| i + 1);
+                (start, len - 1)
+            }
+            // the frame ends with the markers of the seconds 59 and 0
+            Protocol::Wwvb => {
+                if len < 2 || self.symbols[len - 2..] != [Symbol::Marker, Symbol::Marker] {
+                    return None;
+                }
+                (len.saturating_sub(61), len - 1)
+            }
+        };
+        let frame: Vec<Symbol, U64> = self.symbols[start..end].iter().cloned().collect();
+        self.symbols = Vec::new();
+        self.symbols.push(Symbol::Marker).unwrap();
+        match self.protocol {
+            // the frame encodes the minute beginning at its end
+            Protocol::Dcf77 => Some((dcf77(&frame)?, 0)),
+            // the marker is known at the beginning of the second 1
+            Protocol::Msf => Some((msf(&frame)?, 1)),
+            // the frame encodes its own minute
+            Protocol::Wwvb => Some((wwvb(&frame)?, 61)),
+        }
+    }
+}
+
+/// The first bit of each second, `None` if a symbol is not a bit.
+fn bits(frame: &[Symbol]) -> Option<Vec<bool, U64>> {
+    frame
+        .iter()
+        .map(|s| match s {
+            Symbol::Bits(a, _) => Some(*a),
+            _ => None,
+        })
+        .collect()
+}
+/// The value of binary coded decimal bits, from their weights. `None`
+/// if a decimal digit is over 9.
+fn bcd(bits: &[bool], weights: &[u16]) -> Option<u16> {
+    let mut digits = [0; 3];
+    for (&bit, &weight) in bits.iter().zip(weights) {
+        if bit {
+            let i = if weight < 10 {
+                0
+            } else if weight < 100 {
+                1
+            } else {
+                2
+            };
+            digits[i] += weight;
+        }
+    }
+    if digits[0] > 9 || digits[1] > 90 || digits[2] > 900 {
+        return None;
+    }
+    Some(digits.iter().sum())
+}
+fn nb_ones(bits: &[bool]) -> usize {
+    bits.iter().filter(|&&b| b).count()
+}
+fn datetime(year: u16, month: u16, day: u16, hour: u16, min: u16) -> Option<DateTime> {
+    Some(DateTime {
+        date: Date::new(year, month as u8, day as u8).ok()?,
+        time: Time::new(hour as u8, min as u8, 0).ok()?,
+    })
+}
+
+/// Seconds 0 to 58, and a 0 for a leap second.
+fn dcf77(frame: &[Symbol]) -> Option<DateTime> {
+    let b = bits(frame)?;
+    if b.len() != 59 && b.len() != 60 || b[0] || !b[20] {
+        return None;
+    }
+    // even parities
+    if nb_ones(&b[21..=28]) % 2 != 0
+        || nb_ones(&b[29..=35]) % 2 != 0
+        || nb_ones(&b[36..=58]) % 2 != 0
+    {
+        return None;
+    }
+    let min = bcd(&b[21..=27], &[1, 2, 4, 8, 10, 20, 40])?;
+    let hour = bcd(&b[29..=34], &[1, 2, 4, 8, 10, 20])?;
+    let day = bcd(&b[36..=41], &[1, 2, 4, 8, 10, 20])?;
+    let dow = bcd(&b[42..=44], &[1, 2, 4])?;
+    let month = bcd(&b[45..=49], &[1, 2, 4, 8, 10])?;
+    let year = bcd(&b[50..=57], &[1, 2, 4, 8, 10, 20, 40, 80])?;
+    let local = datetime(2000 + year, month, day, hour, min)?;
+    if local.day_of_week() as u16 + 1 != dow {
+        return None;
+    }
+    // CEST or CET
+    let offset = match (b[17], b[18]) {
+        (true, false) => 2,
+        (false, true) => 1,
+        _ => return None,
+    };
+    local.add_seconds(-offset * 3600)
+}
+
+/// Seconds 1 to 59, with a second more or less before the second 17
+/// for a leap second.
+fn msf(frame: &[Symbol]) -> Option<DateTime> {
+    if frame.len() < 58 || frame.len() > 60 {
+        return None;
+    }
+    let a = bits(frame)?;
+    let b: Vec<bool, U64> = frame
+        .iter()
+        .map(|s| match s {
+            Symbol::Bits(_, b) => *b,
+            _ => false,
+        })
+        .collect();
+    // indexed from the end, as a leap second shifts the beginning
+    let shift = 60 - frame.len();
+    let a =
+        |range: core::ops::RangeInclusive<usize>| &a[range.start() - shift..=range.end() - shift];
+    let b = |i: usize| b[i - shift];
+    if a(52..=59) != [false, true, true, true, true, true, true, false] {
+        return None;
+    }
+    // odd parities
+    let parities = [(17..=24, 54), (25..=35, 55), (36..=38, 56), (39..=51, 57)];
+    if parities
+        .iter()
+        .any(|(range, i)| (nb_ones(a(range.clone())) + b(*i) as usize) % 2 == 0)
+    {
+        return None;
+    }
+    let year = bcd(a(17..=24), &[80, 40, 20, 10, 8, 4, 2, 1])?;
+    let month = bcd(a(25..=29), &[10, 8, 4, 2, 1])?;
+    let day = bcd(a(30..=35), &[20, 10, 8, 4, 2, 1])?;
+    let dow = bcd(a(36..=38), &[4, 2, 1])?;
+    let hour = bcd(a(39..=44), &[20, 10, 8, 4, 2, 1])?;
+    let min = bcd(a(45..=51), &[40, 20, 10, 8, 4, 2, 1])?;
+    let local = datetime(2000 + year, month, day, hour, min)?;
+    // from 0 for Sunday
+    if (local.day_of_week() as u16 + 1) % 7 != dow {
+        return None;
+    }
+    let offset = if b(58) { 3600 } else { 0 };
+    local.add_seconds(-offset)
+}
+
+/// Seconds 0 to 59.
+fn wwvb(frame: &[Symbol]) -> Option<DateTime> {
+    if frame.len() != 60 {
+        return None;
+    }
+    let markers = [0, 9, 19, 29, 39, 49, 59];
+    let is_marker = |i: &usize| markers.contains(i);
+    if (0..60).any(|i| is_marker(&i) != (frame[i] == Symbol::Marker)) {
+        return None;
+    }
+    let b: Vec<bool, U64> = frame
+        .iter()
+        .map(|s| *s == Symbol::Bits(true, false))
+        .collect();
+    if [4, 10, 11, 14, 20, 21, 24, 34, 35, 44, 54]
+        .iter()
+        .any(|&i| b[i])
+    {
+        return None;
+    }
+    let min = bcd(&b[1..=8], &[40, 20, 10, 0, 8, 4, 2, 1])?;
+    let hour = bcd(&b[12..=18], &[20, 10, 0, 8, 4, 2, 1])?;
+    let day_of_year = bcd(&b[22..=33], &[200, 100, 0, 80, 40, 20, 10, 0, 8, 4, 2, 1])?;
+    let year = bcd(&b[45..=53], &[80, 40, 20, 10, 0, 8, 4, 2, 1])?;
+    let new_year = Date::new(2000 + year, 1, 1).ok()?;
+    if new_year.is_leap_year() != b[55] || day_of_year == 0 {
+        return None;
+    }
+    let date = Date::from_days(new_year.days() + i64::from(day_of_year) - 1)?;
+    if date.year() != new_year.year() {
+        return None;
+    }
+    let time = Time::new(hour as u8, min as u8, 0).ok()?;
+    Some(DateTime { date, time })
+}
+
+/// A receiver module on an input pin, high during the pulses.
+pub struct Receiver<T> {
+    pin: T,
+    level: bool,
+    time: u32,
+    decoder: Decoder,
+}
+impl<T: InputPin<Error = Infallible>> Receiver<T> {
+    pub fn new(pin: T, protocol: Protocol) -> Self {
+        Self {
+            pin,
+            level: false,
+            time: 0,
+            decoder: Decoder::new(protocol),
+        }
+    }
+    /// To call every millisecond.
+    pub fn poll(&mut self) -> Option<Decoded> {
+        self.time = self.time.wrapping_add(1);
+        let level = self.pin.is_high().unwrap();
+        if level == self.level {
+            return None;
+        }
+        self.level = level;
+        self.decoder.edge(self.time, level)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use std::string::ToString;
+    use std::vec::Vec;
+
+    /// Feeds a trace of one character per second, with some jitter on
+    /// the edges, and returns the decoded times.
+    fn feed(protocol: Protocol, trace: &str, start: u32) -> Vec<Decoded> {
+        let mut decoder = Decoder::new(protocol);
+        let mut results = Vec::new();
+        let mut seed = 42u32;
+        let mut jitter = || {
+            seed = seed.wrapping_mul(1_103_515_245).wrapping_add(12345);
+            (seed >> 16) % 30
+        };
+        for (i, c) in trace.chars().enumerate() {
+            let pulses: &[(u32, u32)] = match (protocol, c) {
+                (_, '-') => &[],
+                (Protocol::Dcf77, '0') | (Protocol::Msf, '0') => &[(0, 100)],
+                (Protocol::Dcf77, '1') | (Protocol::Msf, '1') => &[(0, 200)],
+                (Protocol::Msf, '2') => &[(0, 100), (200, 300)],
+                (Protocol::Msf, '3') => &[(0, 300)],
+                (Protocol::Msf, 'M') => &[(0, 500)],
+                (Protocol::Wwvb, '0') => &[(0, 200)],
+                (Protocol::Wwvb, '1') => &[(0, 500)],
+                (Protocol::Wwvb, 'M') => &[(0, 800)],
+                (Protocol::Dcf77, '!') => &[(0, 100), (500, 510)],
+                _ => panic!("bad trace {}", c),
+            };
+            let second = start.wrapping_add(i as u32 * 1000);
+            for &(from, to) in pulses {
+                let from = second.wrapping_add(from + jitter() / 3);
+                let to = second.wrapping_add(to + jitter()).wrapping_sub(15);
+                results.extend(decoder.edge(from, true));
+                assert_eq!(decoder.edge(to, false), None);
+            }
+        }
+        results
+    }
+    fn utc(year: u16, month: u8, day: u8, hour: u8, min: u8, sec: u8) -> DateTime {
+        DateTime {
+            date: Date::new(year, month, day).unwrap(),
+            time: Time::new(hour, min, sec).unwrap(),
+        }
+    }
+    fn decoded(datetime: DateTime, confidence: u8) -> Decoded {
+        Decoded {
+            datetime,
+            confidence,
+        }
+    }
+
+    // 2020-04-24 10:30 and 10:31 CEST
+    const DCF77_1030: &str = "00000000000000000100100001100000010100100110100100000001000-";
+    const DCF77_1031: &str = "00000000000000000100110001101000010100100110100100000001000-";
+    // 2020-04-24 10:30 and 10:31 BST
+    const MSF_1030: &str = "M00000000000000000010000000100100100101010000011000001113130";
+    const MSF_1031: &str = "M00000000000000000010000000100100100101010000011000101113330";
+    // 2020-12-31 23:58 and 23:59 UTC
+    const WWVB_2358: &str = "M10101000M001000011M001100110M011000000M000000010M000001000M";
+    const WWVB_2359: &str = "M10101001M001000011M001100110M011000000M000000010M000001000M";
+
+    #[test]
+    fn test_dcf77() {
+        let trace = ["1000-", DCF77_1030, DCF77_1031, "0"].concat();
+        assert_eq!(
+            feed(Protocol::Dcf77, &trace, 0),
+            [
+                decoded(utc(2020, 4, 24, 8, 30, 0), 1),
+                decoded(utc(2020, 4, 24, 8, 31, 0), 2),
+            ]
+        );
+        // across the wrapping of the timestamps
+        let results = feed(Protocol::Dcf77, &trace, u32::max_value() - 30_000);
+        assert_eq!(results.len(), 2);
+        assert_eq!(results[1].confidence, 2);
+
+        // 2019-12-31 23:59 CET
+        let trace = "0-00000000000000000010110011010110001110001101001001100110001-0";
+        assert_eq!(
+            feed(Protocol::Dcf77, trace, 0),
+            [decoded(utc(2019, 12, 31, 22, 59, 0), 1)]
+        );
+    }
+
+    #[test]
+    fn test_dcf77_errors() {
+        // a bit of the minutes flipped, breaking the parity
+        let mut bad = DCF77_1030.to_string();
+        bad.replace_range(22..23, "1");
+        let trace = ["1000-", &bad, DCF77_1031, DCF77_1030, "0"].concat();
+        // the last frame is not consistent with the one before
+        assert_eq!(
+            feed(Protocol::Dcf77, &trace, 0),
+            [
+                decoded(utc(2020, 4, 24, 8, 31, 0), 1),
+                decoded(utc(2020, 4, 24, 8, 30, 0), 1),
+            ]
+        );
+        // some noise during a second
+        let mut noisy = DCF77_1030.to_string();
+        noisy.replace_range(40..41, "!");
+        let trace = ["1000-", &noisy, DCF77_1031, "0"].concat();
+        assert_eq!(
+            feed(Protocol::Dcf77, &trace, 0),
+            [decoded(utc(2020, 4, 24, 8, 31, 0), 1)]
+        );
+        // a missing pulse
+        let mut missing = DCF77_1030.to_string();
+        missing.replace_range(10..11, "-");
+        let trace = ["1000-", &missing, DCF77_1031, "0"].concat();
+        assert_eq!(feed(Protocol::Dcf77, &trace, 0).len(), 1);
+    }
+
+    #[test]
+    fn test_msf() {
+        let trace = ["0000", MSF_1030, MSF_1031, "M0"].concat();
+        assert_eq!(
+            feed(Protocol::Msf, &trace, 0),
+            [
+                decoded(utc(2020, 4, 24, 9, 30, 1), 1),
+                decoded(utc(2020, 4, 24, 9, 31, 1), 2),
+            ]
+        );
+        // a parity bit flipped
+        let mut bad = MSF_1031.to_string();
+        bad.replace_range(57..58, "1");
+        let trace = ["0000", MSF_1030, &bad, "M0"].concat();
+        assert_eq!(
+            feed(Protocol::Msf, &trace, 0),
+            [decoded(utc(2020, 4, 24, 9, 30, 1), 1)]
+        );
+    }
+
+    #[test]
+    fn test_wwvb() {
+        let trace = ["0M", WWVB_2358, WWVB_2359, "M0"].concat();
+        assert_eq!(
+            feed(Protocol::Wwvb, &trace, 0),
+            [
+                decoded(utc(2020, 12, 31, 23, 59, 1), 1),
+                decoded(utc(2021, 1, 1, 0, 0, 1), 2),
+            ]
+        );
+        // a misplaced marker
+        let mut bad = WWVB_2359.to_string();
+        bad.replace_range(30..31, "M");
+        let trace = ["0M", WWVB_2358, &bad, "M0"].concat();
+        assert_eq!(
+            feed(Protocol::Wwvb, &trace, 0),
+            [decoded(utc(2020, 12, 31, 23, 59, 1), 1)]
+        );
+    }
+
+    #[test]
+    fn test_bcd() {
+        assert_eq!(
+            bcd(&[true, false, false, true, true], &[1, 2, 4, 8, 10]),
+            Some(19)
+        );
+        assert_eq!(bcd(&[false, true, false, true], &[1, 2, 4, 8]), None);
+    }
+}
@@ -0,0 +1,127 @@
+//! User settings, saved across resets.
+
+use crate::alarm::{DEFAULT_GRACE_MINS, MAX_GRACE_MINS};
+use crate::calendars::SecondaryCalendar;
+use crate::holidays::Holidays;
+use crate::locale::{Locale, TimeFormat};
+use crate::sun::Location;
+use heapless::{consts::*, Vec};
+
+/// Marks valid settings, the low byte is the version of the format.
+const MAGIC: u16 = 0x5e00;
+const VERSION: u16 = 1;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Settings {
+    pub locale: Locale,
+    pub time_format: TimeFormat,
+    pub location: Location,
+    pub calendar: SecondaryCalendar,
+    pub holidays: Holidays,
+    /// The grace period of the late alarms, see `AlarmManager`.
+    pub grace_mins: u8,
+}
+impl Default for Settings {
+    fn default() -> Self {
+        Self {
+            locale: Locale::default(),
+            time_format: TimeFormat::default(),
+            location: Location::default(),
+            calendar: SecondaryCalendar::default(),
+            holidays: Holidays::default(),
+            grace_mins: DEFAULT_GRACE_MINS,
+        }
+    }
+}
+impl Settings {
+    /// Serializes the settings as half words, the unit of the flash.
+    pub fn as_u16s(&self) -> Vec<u16, U16> {
+        let mut v = Vec::new();
+        v.push(MAGIC | VERSION).unwrap();
+        v.push(u16::from(self.locale.as_u8())).unwrap();
+        v.push(u16::from(self.time_format.as_u8())).unwrap();
+        // shifted to be positive, `0xffff` being invalid
+        let location = self.location;
+        v.push((location.latitude + Location::MAX_LATITUDE) as u16)
+            .unwrap();
+        v.push((location.longitude + Location::MAX_LONGITUDE) as u16)
+            .unwrap();
+        v.push(u16::from(self.calendar.as_u8())).unwrap();
+        v.push(u16::from(self.holidays.as_u8())).unwrap();
+        v.push(u16::from(self.grace_mins)).unwrap();
+        v
+    }
+    /// Returns `None` if `data` does not begin with valid settings, as
+    /// an erased flash page. The fields added after the first version
+    /// take their default value when missing, as they are erased
+    /// (`0xffff`) in the settings saved by an older firmware.
+    pub fn try_from(data: &[u16]) -> Option<Self> {
+        let (&magic, data) = data.split_first()?;
+        if magic != MAGIC | VERSION {
+            return None;
+        }
+        let field = |i: usize| data.get(i).map(|&u| u as u8);
+        Some(Self {
+            locale: Locale::try_from(field(0)?)?,
+            time_format: field(1).and_then(TimeFormat::try_from).unwrap_or_default(),
+            location: location(data.get(2..4)).unwrap_or_default(),
+            calendar: field(4)
+                .and_then(SecondaryCalendar::try_from)
+                .unwrap_or_default(),
+            holidays: field(5).and_then(Holidays::try_from).unwrap_or_default(),
+            grace_mins: data
+                .get(6)
+                .filter(|&&u| u <= u16::from(MAX_GRACE_MINS))
+                .map_or(DEFAULT_GRACE_MINS, |&u| u as u8),
+        })
+    }
+}
+
+fn location(data: Option<&[u16]>) -> Option<Location> {
+    let shift = |u: u16, max: i16| Some(u as i16 - max).filter(|v| v.abs() <= max);
+    let data = data?;
+    Some(Location {
+        latitude: shift(data[0], Location::MAX_LATITUDE)?,
+        longitude: shift(data[1], Location::MAX_LONGITUDE)?,
+    })
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_round_trip() {
+        let settings = Settings {
+            locale: Locale::German,
+            time_format: TimeFormat::TwelveHour,
+            location: Location {
+                latitude: -900,
+                longitude: 1800,
+            },
+            calendar: SecondaryCalendar::Chinese,
+            holidays: Holidays::None,
+            grace_mins: 0,
+        };
+        assert_eq!(Settings::try_from(&settings.as_u16s()), Some(settings));
+        let german = Settings {
+            locale: Locale::German,
+            ..Settings::default()
+        };
+        assert_eq!(
+            Settings::try_from(&[MAGIC | VERSION, 2]),
+            Some(german.clone())
+        );
+        assert_eq!(
+            Settings::try_from(&[MAGIC | VERSION, 2, 0xffff]),
+            Some(german.clone())
+        );
+        let mut data = german.as_u16s();
+        data[7] = u16::from(MAX_GRACE_MINS) + 1;
+        assert_eq!(Settings::try_from(&data), Some(german));
+        assert_eq!(Settings::try_from(&[0xffff; 16]), None);
+        assert_eq!(Settings::try_from(&[MAGIC | VERSION]), None);
+        assert_eq!(Settings::try_from(&[MAGIC | VERSION, 42]), None);
+        assert_eq!(Settings::try_from(&[]), None);
+    }
+}
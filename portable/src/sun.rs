@@ -0,0 +1,243 @@
+//! Sunrise, sunset and twilight times.
+//!
+//! Uses the sunrise equation, see
+//! https://en.wikipedia.org/wiki/Sunrise_equation, accurate to about a
+//! minute between the polar circles. The computations are done in
+//! `f32` on small numbers, the day being kept as an integer.
+
+use crate::datetime::DateTime;
+use core::fmt;
+use libm::{acosf, asinf, cosf, sinf};
+
+/// 2000-01-01 in days since 1970-01-01, J2000.0 being at noon.
+const J2000: i64 = 10_957;
+/// Altitude of the center of the sun at sunrise, taking the refraction
+/// and the radius of the sun into account.
+const SUNRISE_ALTITUDE: f32 = -0.833;
+/// Altitude of the center of the sun at the beginning and the end of
+/// the civil twilight.
+const CIVIL_TWILIGHT_ALTITUDE: f32 = -6.;
+
+/// A position on Earth, in tenths of degree, north and east being
+/// positive.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct Location {
+    pub latitude: i16,
+    pub longitude: i16,
+}
+impl Default for Location {
+    /// Paris, as the default time zone.
+    fn default() -> Self {
+        Self {
+            latitude: 489,
+            longitude: 23,
+        }
+    }
+}
+impl Location {
+    pub const MAX_LATITUDE: i16 = 900;
+    pub const MAX_LONGITUDE: i16 = 1800;
+
+    pub fn latitude_degrees(self) -> f32 {
+        f32::from(self.latitude) / 10.
+    }
+    pub fn longitude_degrees(self) -> f32 {
+        f32::from(self.longitude) / 10.
+    }
+}
+impl fmt::Display for Location {
+    /// As `48.9N 2.3E`.
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let coord = |f: &mut fmt::Formatter, v: i16, pos, neg| {
+            let dir = if v < 0 { neg } else { pos };
+            let v = i32::from(v).abs();
+            write!(f, "{}.{}{}", v / 10, v % 10, dir)
+        };
+        coord(f, self.latitude, 'N', 'S')?;
+        f.write_str(" ")?;
+        coord(f, self.longitude, 'E', 'W')
+    }
+}
+
+/// The times of the sun for a day, in UTC.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SunTimes {
+    /// The sun is at its highest.
+    pub noon: DateTime,
+    /// `None` when the sun does not rise or does not set this day.
+    pub sunrise: Option<DateTime>,
+    pub sunset: Option<DateTime>,
+    /// Beginning of the civil twilight, `None` when the sun never goes
+    /// 6° below or never goes above the horizon.
+    pub dawn: Option<DateTime>,
+    /// End of the civil twilight.
+    pub dusk: Option<DateTime>,
+}
+impl SunTimes {
+    /// The times of the solar day around the noon of `date`, `date`
+    /// being a local date. Latitude and longitude are in degrees.
+    /// Returns `None` if the times can not be represented.
+    pub fn new(date: &DateTime, latitude: f32, longitude: f32) -> Option<SunTimes> {
+        let day = date.date.days() - J2000;
+        // days since J2000.0 of the mean solar noon
+        let mean_noon = day as f32 - longitude / 360.;
+        let anomaly = normalize(357.5291 + 0.985_600_3 * mean_noon).to_radians();
+        let center =
+            1.9148 * sinf(anomaly) + 0.0200 * sinf(2. * anomaly) + 0.0003 * sinf(3. * anomaly);
+        let ecliptic_longitude =
+            normalize(anomaly.to_degrees() + center + 180. + 102.9372).to_radians();
+        // the day is kept apart to keep the precision of the fraction
+        let noon =
+            -longitude / 360. + 0.0053 * sinf(anomaly) - 0.0069 * sinf(2. * ecliptic_longitude);
+        let declination = asinf(sinf(ecliptic_longitude) * sinf(23.4397f32.to_radians()));
+
+        let to_datetime = |fraction: f32| {
+            let secs = libm::roundf((fraction + 0.5) * 86400.) as i64;
+            DateTime::from_timestamp((J2000 + day) * 86400 + secs)
+        };
+        let hour_angle = |altitude: f32| {
+            let latitude = latitude.to_radians();
+            let cos = (sinf(altitude.to_radians()) - sinf(latitude) * sinf(declination))
+                / (cosf(latitude) * cosf(declination));
+            if (-1. ..=1.).contains(&cos) {
+                Some(acosf(cos).to_degrees() / 360.)
+            } else {
+                None
+            }
+        };
+        let rise = |altitude| hour_angle(altitude).and_then(|a| to_datetime(noon - a));
+        let set = |altitude| hour_angle(altitude).and_then(|a| to_datetime(noon + a));
+
+        Some(SunTimes {
+            noon: to_datetime(noon)?,
+            sunrise: rise(SUNRISE_ALTITUDE),
+            sunset: set(SUNRISE_ALTITUDE),
+            dawn: rise(CIVIL_TWILIGHT_ALTITUDE),
+            dusk: set(CIVIL_TWILIGHT_ALTITUDE),
+        })
+    }
+}
+
+/// The apparent longitude of the sun in degrees at the timestamp `t`,
+/// accurate to about 0.01°, see the chapter 25 of Astronomical
+/// Algorithms by Jean Meeus.
+pub(crate) fn solar_longitude(t: i64) -> f32 {
+    // J2000.0 in terrestrial time, about 64s ahead of UTC
+    let secs = t - 946_727_936;
+    let (days, secs) = (secs.div_euclid(86400), secs.rem_euclid(86400));
+    // in integer nano degrees to keep the precision over the centuries
+    let angle = |base: i64, per_day: i64| {
+        let nano_degrees = base + days * per_day + secs * per_day / 86400;
+        nano_degrees.rem_euclid(360_000_000_000) as f32 / 1e9
+    };
+    let centuries = days as f32 / 36525.;
+    let mean_longitude = angle(280_466_460_000, 985_647_360);
+    let anomaly = angle(357_529_110_000, 985_600_282).to_radians();
+    let center = (1.914_602 - 0.004_817 * centuries) * sinf(anomaly)
+        + (0.019_993 - 0.000_101 * centuries) * sinf(2. * anomaly)
+        + 0.000_289 * sinf(3. * anomaly);
+    // nutation and aberration
+    let node = angle(125_040_000_000, -52_953_757).to_radians();
+    normalize(mean_longitude + center - 0.005_69 - 0.004_78 * sinf(node))
+}
+
+/// Returns the angle in degrees in `0..360`.
+fn normalize(degrees: f32) -> f32 {
+    let r = libm::fmodf(degrees, 360.);
+    if r < 0. {
+        r + 360.
+    } else {
+        r
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::datetime::{Date, Time};
+
+    fn date(year: u16, month: u8, day: u8) -> DateTime {
+        DateTime {
+            date: Date::new(year, month, day).unwrap(),
+            time: Time::MIDNIGHT,
+        }
+    }
+    /// Checks that `dt` is `hour:min` UTC, plus or minus a minute.
+    fn assert_near(dt: Option<DateTime>, hour: u8, min: u8) {
+        let dt = dt.unwrap();
+        let expected = DateTime {
+            time: Time::new(hour, min, 0).unwrap(),
+            ..dt
+        };
+        let diff = dt.sub(&expected).as_secs().abs();
+        assert!(diff <= 90, "{} is not {:02}:{:02}", dt, hour, min);
+    }
+
+    #[test]
+    fn test_paris() {
+        let paris = Location::default();
+        let (lat, lon) = (paris.latitude_degrees(), paris.longitude_degrees());
+
+        let summer = SunTimes::new(&date(2020, 6, 21), lat, lon).unwrap();
+        assert_near(summer.sunrise, 3, 47);
+        assert_near(summer.sunset, 19, 58);
+        assert_near(Some(summer.noon), 11, 52);
+        assert_near(summer.dawn, 3, 5);
+        assert_near(summer.dusk, 20, 40);
+
+        let winter = SunTimes::new(&date(2019, 12, 21), lat, lon).unwrap();
+        assert_near(winter.sunrise, 7, 42);
+        assert_near(winter.sunset, 15, 56);
+        assert_near(Some(winter.noon), 11, 49);
+    }
+
+    #[test]
+    fn test_southern_hemisphere() {
+        // Sydney
+        let times = SunTimes::new(&date(2020, 1, 1), -33.9, 151.2).unwrap();
+        assert_near(times.sunrise, 18, 47);
+        assert_eq!(times.sunrise.unwrap().day(), 31);
+        assert_near(times.sunset, 9, 10);
+        assert_eq!(times.sunset.unwrap().day(), 1);
+    }
+
+    #[test]
+    fn test_polar() {
+        // Tromsø
+        let (lat, lon) = (69.6, 18.9);
+        let night = SunTimes::new(&date(2019, 12, 21), lat, lon).unwrap();
+        assert_eq!(night.sunrise, None);
+        assert_eq!(night.sunset, None);
+        assert!(night.dawn.is_some());
+        let day = SunTimes::new(&date(2020, 6, 21), lat, lon).unwrap();
+        assert_eq!(day.sunrise, None);
+        assert_eq!(day.dusk, None);
+    }
+
+    #[test]
+    fn test_solar_longitude() {
+        let at = |year, month, day, hour, min| {
+            let date = date(year, month, day);
+            let time = Time::new(hour, min, 0).unwrap();
+            solar_longitude(DateTime { time, ..date }.timestamp())
+        };
+        // the equinoxes and solstices of 2020, and the solstice of 1999
+        let equinox = at(2020, 3, 20, 3, 50);
+        assert!(!(0.02..=359.98).contains(&equinox), "{}", equinox);
+        assert!((at(2020, 6, 20, 21, 44) - 90.).abs() < 0.02);
+        assert!((at(2020, 9, 22, 13, 31) - 180.).abs() < 0.02);
+        assert!((at(2020, 12, 21, 10, 2) - 270.).abs() < 0.02);
+        assert!((at(1999, 12, 22, 7, 44) - 270.).abs() < 0.02);
+    }
+
+    #[test]
+    fn test_location() {
+        use std::string::ToString;
+        assert_eq!(Location::default().to_string(), "48.9N 2.3E");
+        let location = Location {
+            latitude: -339,
+            longitude: -5,
+        };
+        assert_eq!(location.to_string(), "33.9S 0.5W");
+    }
+}
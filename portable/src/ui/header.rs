@@ -8,6 +8,13 @@ const MARGIN: i32 = 0;
 const FONT_HEIGHT: i32 = 16;
 const FONT_WIDTH: i32 = 8;
 const BOTTOM_Y: i32 = 128 - MARGIN - FONT_HEIGHT;
+/// The number of characters on a line.
+pub const NB_COLUMNS: usize = (296 - 2 * MARGIN) as usize / FONT_WIDTH as usize;
+
+/// Whether `left` and `right` fit on a line, with a space between them.
+pub fn fits(left: &str, right: &str) -> bool {
+    left.chars().count() + 1 + right.chars().count() <= NB_COLUMNS
+}
 
 pub struct Header<'a> {
     display: &'a mut Display2in9,
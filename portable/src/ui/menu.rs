@@ -47,6 +47,19 @@ pub fn render(title: &str, mut items: &[&str], mut selected: i32, display: &mut
     );
 }
 
+/// Renders `lines` as a menu without selection, at most 5 lines.
+pub fn render_lines(title: &str, lines: &[&str], display: &mut Display2in9) {
+    render_str(title, MARGIN_LEFT, MARGIN_TOP, display);
+    for (i, &line) in lines.iter().take(5).enumerate() {
+        render_str(
+            line,
+            MARGIN_LEFT + 3 * FONT_WIDTH,
+            MARGIN_TOP + (1 + i as i32) * INTERLINE,
+            display,
+        );
+    }
+}
+
 fn render_str(s: &str, x: i32, y: i32, display: &mut Display2in9) {
     display.draw(
         Font8x16::render_str(s)
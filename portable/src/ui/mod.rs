@@ -1,5 +1,13 @@
-use crate::alarm::{Alarm, AlarmManager};
-use crate::datetime;
+use crate::alarm::{Alarm, AlarmManager, MaxAlarms, MAX_GRACE_MINS, MAX_VACATION_DAYS};
+use crate::calendars::SecondaryCalendar;
+use crate::datetime::{self, Duration};
+use crate::holidays::Holidays;
+use crate::locale::Locale;
+use crate::moon::Moon;
+use crate::nmea::GpsTime;
+use crate::radio;
+use crate::settings::Settings;
+use crate::sun::SunTimes;
 use core::fmt::{self, Write};
 use embedded_graphics::coord::Coord;
 use embedded_graphics::fonts::Font8x16;
@@ -11,19 +19,30 @@ use heapless::{consts::*, String, Vec};
 
 mod header;
 mod menu;
+mod moon;
 mod seven_segments;
 mod state;
 
 #[derive(Debug)]
 pub enum Msg {
     DateTime(datetime::DateTime),
+    /// The seconds since the boot, not changed by the setting of the
+    /// clock.
+    Uptime(u32),
     Environment(Environment),
     FailEnvironment,
+    /// The settings or the alarms could not be saved in the flash.
+    FailStorage,
     ButtonCancel,
     ButtonMinus,
     ButtonPlus,
     ButtonOk,
-    AlarmManager(AlarmManager),
+    /// An alarm starts ringing, by ID.
+    AlarmRinging(usize),
+    /// The ringing ended by itself, no button pressed.
+    SoundEnded,
+    RadioTime(radio::Decoded),
+    GpsTime(GpsTime),
 }
 impl Msg {
     fn is_button(&self) -> bool {
@@ -37,33 +56,56 @@ impl Msg {
 
 #[derive(Debug)]
 pub enum Cmd {
-    UpdateRtc(datetime::DateTime),
+    /// The new time, in UTC, and whether it comes from a precise source,
+    /// the radio or the GPS, to measure the drift of the RTC.
+    UpdateRtc(datetime::DateTime, bool),
     UpdateAlarm(Alarm, usize),
+    AddAlarm(Alarm),
+    RemoveAlarm(usize),
+    /// Skips the next ring of an alarm, or not.
+    SkipAlarm(usize, bool),
+    /// Suspends the alarms until the end of the date, or resumes them.
+    SetVacation(Option<datetime::Date>),
+    UpdateSettings(Settings),
+    /// Snoozes the ringing alarm at the given uptime.
+    SnoozeAlarm(u32),
+    DismissAlarm,
+    /// Acknowledges the missed alarm.
+    ClearMissed,
+    StopSound,
     FullUpdate,
 }
 
 #[derive(Clone)]
 pub struct Model {
+    tz: datetime::TimeZone,
+    settings: Settings,
     now: datetime::DateTime,
+    uptime: u32,
+    /// The uptime of the last button press.
     last_input: u32,
     env: Environment,
     nb_fail_environment: u32,
-    alarm_manager: AlarmManager,
+    /// A save in the flash failed, until cancel is pressed.
+    fail_storage: bool,
     screen: state::Screen,
 }
 
 impl Model {
-    pub fn init() -> Self {
+    pub fn init(tz: datetime::TimeZone, settings: Settings) -> Self {
         Self {
+            tz,
+            settings,
             now: datetime::DateTime::new(0),
+            uptime: 0,
             last_input: 0,
             env: Default::default(),
             nb_fail_environment: 0,
-            alarm_manager: AlarmManager::default(),
+            fail_storage: false,
             screen: state::Screen::Clock,
         }
     }
-    pub fn update(&mut self, msg: Msg) -> Vec<Cmd, U4> {
+    pub fn update(&mut self, msg: Msg, alarm_manager: &AlarmManager) -> Vec<Cmd, U4> {
         use self::state::Screen::*;
         let mut cmds = Vec::new();
 
@@ -74,45 +116,134 @@ impl Model {
         match msg {
             Msg::DateTime(dt) => {
                 self.now = dt;
-                if self.screen != state::Screen::Clock
-                    && self
-                        .now
-                        .to_epoch()
-                        .map_or(false, |n| n - self.last_input > 10 * 60)
-                {
-                    self.screen = state::Screen::Clock;
-                }
-                if self.now.hour == 0 && self.now.min == 0 && self.now.sec == 0 {
+                if self.now.time == datetime::Time::MIDNIGHT {
                     cmds.push(Cmd::FullUpdate).unwrap();
                 }
             }
+            Msg::Uptime(secs) => {
+                self.uptime = secs;
+                let idle = Duration::from_secs(i64::from(secs.wrapping_sub(self.last_input)));
+                // a ringing alarm waits for a button
+                let timeout = match self.screen {
+                    Clock | Ringing(_) => false,
+                    _ => idle > Duration::from_mins(10),
+                };
+                if timeout {
+                    self.screen = Clock;
+                }
+            }
             Msg::Environment(measurements) => {
                 self.env = measurements;
                 self.nb_fail_environment = 0;
             }
             Msg::FailEnvironment => self.nb_fail_environment += 1,
-            Msg::AlarmManager(am) => self.alarm_manager = am,
+            Msg::FailStorage => self.fail_storage = true,
+            Msg::AlarmRinging(id) => {
+                self.screen = Ringing(id);
+                cmds.push(Cmd::FullUpdate).unwrap();
+            }
+            Msg::SoundEnded => {
+                // nobody there to snooze
+                if let Ringing(_) = self.screen {
+                    self.screen = Clock;
+                    cmds.push(Cmd::DismissAlarm).unwrap();
+                    cmds.push(Cmd::FullUpdate).unwrap();
+                }
+            }
+            Msg::RadioTime(decoded) => {
+                // a frame alone may pass the parity checks by chance
+                if decoded.confidence >= 2 {
+                    cmds.extend(self.correct_rtc(&decoded.datetime));
+                }
+            }
+            Msg::GpsTime(time) => {
+                if time.valid {
+                    cmds.extend(self.correct_rtc(&time.datetime));
+                }
+            }
             Msg::ButtonOk => {
                 use self::state::{EditDateTime, MenuElt};
                 self.screen = match ::core::mem::replace(&mut self.screen, Clock) {
                     Clock => Menu(MenuElt::Clock),
                     Menu(MenuElt::Clock) => Clock,
                     Menu(MenuElt::SetClock) => {
-                        let mut dt = self.now.clone();
-                        dt.sec = 0;
+                        let mut dt = self.now;
+                        dt.time = datetime::Time::new(dt.hour(), dt.minute(), 0).unwrap();
                         SetClock(EditDateTime::new(dt))
                     }
                     Menu(MenuElt::ManageAlarms) => ManageAlarms(0),
+                    Menu(MenuElt::SetLocale) => SetLocale(self.settings.locale),
+                    Menu(MenuElt::ToggleTimeFormat) => {
+                        self.settings.time_format = self.settings.time_format.toggle();
+                        cmds.push(Cmd::UpdateSettings(self.settings.clone()))
+                            .unwrap();
+                        Menu(MenuElt::ToggleTimeFormat)
+                    }
+                    Menu(MenuElt::SetLocation) => {
+                        SetLocation(state::EditLocation::new(self.settings.location))
+                    }
+                    Menu(MenuElt::SetCalendar) => SetCalendar(self.settings.calendar),
+                    Menu(MenuElt::SetHolidays) => SetHolidays(self.settings.holidays),
+                    Menu(MenuElt::SetGrace) => SetGrace(self.settings.grace_mins),
+                    Menu(MenuElt::SetVacation) => SetVacation(alarm_manager.on_vacation(&self.now)),
                     SetClock(mut edit) => {
                         if let Some(dt) = edit.ok() {
-                            cmds.push(Cmd::UpdateRtc(dt)).unwrap();
+                            let utc = self.tz.to_utc(&dt);
+                            if let Some(utc) = datetime::DateTime::from_timestamp(utc) {
+                                cmds.push(Cmd::UpdateRtc(utc, false)).unwrap();
+                            }
                             Clock
                         } else {
                             SetClock(edit)
                         }
                     }
-                    ManageAlarms(i) => ManageAlarm(state::ManageAlarm::new(&self.alarm_manager, i)),
+                    ManageAlarms(i) => {
+                        ManageAlarm(state::ManageAlarm::new(alarm_manager, i, self.now.date))
+                    }
                     ManageAlarm(state) => state.ok(&mut cmds),
+                    SetLocale(locale) => {
+                        self.settings.locale = locale;
+                        cmds.push(Cmd::UpdateSettings(self.settings.clone()))
+                            .unwrap();
+                        Clock
+                    }
+                    SetLocation(mut edit) => {
+                        if let Some(location) = edit.ok() {
+                            self.settings.location = location;
+                            cmds.push(Cmd::UpdateSettings(self.settings.clone()))
+                                .unwrap();
+                            Clock
+                        } else {
+                            SetLocation(edit)
+                        }
+                    }
+                    SetCalendar(calendar) => {
+                        self.settings.calendar = calendar;
+                        cmds.push(Cmd::UpdateSettings(self.settings.clone()))
+                            .unwrap();
+                        Clock
+                    }
+                    SetHolidays(holidays) => {
+                        self.settings.holidays = holidays;
+                        cmds.push(Cmd::UpdateSettings(self.settings.clone()))
+                            .unwrap();
+                        Clock
+                    }
+                    SetGrace(mins) => {
+                        self.settings.grace_mins = mins;
+                        cmds.push(Cmd::UpdateSettings(self.settings.clone()))
+                            .unwrap();
+                        Clock
+                    }
+                    SetVacation(until) => {
+                        cmds.push(Cmd::SetVacation(until)).unwrap();
+                        Clock
+                    }
+                    Ringing(_) => {
+                        cmds.push(Cmd::StopSound).unwrap();
+                        cmds.push(Cmd::SnoozeAlarm(self.uptime)).unwrap();
+                        Clock
+                    }
                 };
                 if let Clock = self.screen {
                     cmds.push(Cmd::FullUpdate).unwrap();
@@ -120,11 +251,28 @@ impl Model {
             }
             Msg::ButtonCancel => {
                 self.screen = match ::core::mem::replace(&mut self.screen, Clock) {
-                    Clock => Clock,
+                    Clock => {
+                        if alarm_manager.missed.is_some() {
+                            cmds.push(Cmd::ClearMissed).unwrap();
+                        }
+                        self.fail_storage = false;
+                        Clock
+                    }
                     Menu(mut state) => state.cancel(),
                     SetClock(mut state) => state.cancel(),
                     ManageAlarms(_) => Menu(state::MenuElt::ManageAlarms),
                     ManageAlarm(mut state) => state.cancel(),
+                    SetLocale(_) => Menu(state::MenuElt::SetLocale),
+                    SetLocation(mut state) => state.cancel(),
+                    SetCalendar(_) => Menu(state::MenuElt::SetCalendar),
+                    SetHolidays(_) => Menu(state::MenuElt::SetHolidays),
+                    SetGrace(_) => Menu(state::MenuElt::SetGrace),
+                    SetVacation(_) => Menu(state::MenuElt::SetVacation),
+                    Ringing(_) => {
+                        cmds.push(Cmd::StopSound).unwrap();
+                        cmds.push(Cmd::DismissAlarm).unwrap();
+                        Clock
+                    }
                 };
                 if let Clock = self.screen {
                     cmds.push(Cmd::FullUpdate).unwrap();
@@ -134,71 +282,111 @@ impl Model {
                 Clock => {}
                 Menu(state) => state.next(),
                 SetClock(state) => state.next(),
-                ManageAlarms(i) => *i = (*i + 1) % self.alarm_manager.alarms.len(),
+                ManageAlarms(i) => *i = (*i + 1) % nb_manage_alarms(alarm_manager),
                 ManageAlarm(state) => state.next(),
+                SetLocale(locale) => *locale = locale.next(),
+                SetLocation(state) => state.next(),
+                SetCalendar(calendar) => *calendar = calendar.next(),
+                SetHolidays(holidays) => *holidays = holidays.next(),
+                SetGrace(mins) => *mins = (*mins + 1) % (MAX_GRACE_MINS + 1),
+                SetVacation(until) => {
+                    let from = until.unwrap_or(self.now.date).days();
+                    let days = if until.is_some() { from + 1 } else { from };
+                    // up to the last day kept in the backup domain
+                    *until = datetime::Date::from_days(days)
+                        .filter(|_| days <= MAX_VACATION_DAYS)
+                        .or(*until);
+                }
+                Ringing(_) => {}
             },
             Msg::ButtonMinus => match &mut self.screen {
                 Clock => {}
                 Menu(state) => state.prev(),
                 SetClock(state) => state.prev(),
                 ManageAlarms(i) => {
-                    let len = self.alarm_manager.alarms.len();
+                    let len = nb_manage_alarms(alarm_manager);
                     *i = (*i + len - 1) % len;
                 }
                 ManageAlarm(state) => state.prev(),
+                SetLocale(locale) => *locale = locale.prev(),
+                SetLocation(state) => state.prev(),
+                SetCalendar(calendar) => *calendar = calendar.prev(),
+                SetHolidays(holidays) => *holidays = holidays.prev(),
+                SetGrace(mins) => *mins = (*mins + MAX_GRACE_MINS) % (MAX_GRACE_MINS + 1),
+                // before today, no vacation
+                SetVacation(until) => {
+                    let today = self.now.date;
+                    *until = until
+                        .and_then(|until| datetime::Date::from_days(until.days() - 1))
+                        .filter(|&until| until >= today)
+                }
+                Ringing(_) => {}
             },
         }
         cmds
     }
-    pub fn view(&self) -> Display2in9 {
+    pub fn view(&self, alarm_manager: &AlarmManager) -> Display2in9 {
         let mut display = Display2in9::default();
         display.set_rotation(DisplayRotation::Rotate270);
 
-        self.render_header(&mut display);
+        self.render_header(alarm_manager, &mut display);
 
         use self::state::Screen::*;
         match &self.screen {
             Clock => self.render_clock(&mut display),
             Menu(elt) => self.render_menu(*elt, &mut display),
             SetClock(datetime) => self.render_set_clock(datetime, &mut display),
-            ManageAlarms(i) => self.render_manage_alarms(*i, &mut display),
-            ManageAlarm(state) => state.render(&mut display),
+            ManageAlarms(i) => self.render_manage_alarms(alarm_manager, *i, &mut display),
+            ManageAlarm(state) => {
+                let settings = &self.settings;
+                state.render(settings.locale, settings.time_format, &mut display)
+            }
+            SetLocale(locale) => self.render_set_locale(*locale, &mut display),
+            SetLocation(edit) => self.render_set_location(edit, &mut display),
+            SetCalendar(calendar) => self.render_set_calendar(*calendar, &mut display),
+            SetHolidays(holidays) => self.render_set_holidays(*holidays, &mut display),
+            SetGrace(mins) => self.render_set_grace(*mins, &mut display),
+            SetVacation(until) => self.render_set_vacation(*until, &mut display),
+            Ringing(id) => self.render_ringing(alarm_manager, *id, &mut display),
         }
 
         display
     }
     fn update_last_input(&mut self) {
-        if let Some(epoch) = self.now.to_epoch() {
-            self.last_input = epoch;
+        self.last_input = self.uptime;
+    }
+    /// Sets the RTC to `utc` from a time source, only when it is off as
+    /// the time source is read every second.
+    fn correct_rtc(&self, utc: &datetime::DateTime) -> Option<Cmd> {
+        let local = self.tz.to_local(utc.timestamp())?;
+        if local.sub(&self.now).abs() > Duration::from_secs(1) {
+            Some(Cmd::UpdateRtc(*utc, true))
+        } else {
+            None
         }
     }
-    fn render_header(&self, display: &mut Display2in9) {
+    fn render_header(&self, alarm_manager: &AlarmManager, display: &mut Display2in9) {
+        let locale = self.settings.locale;
         let mut header = header::Header::new(display);
         let mut s: String<U128> = String::new();
 
+        // the short day of the week keeps the date and the week clear of
+        // the humidity and temperature at the top right
         write!(
             s,
-            "{:4}-{:02}-{:02} {}",
-            self.now.year, self.now.month, self.now.day, self.now.day_of_week,
+            "{:4}-{:02}-{:02} {} W{:02}",
+            self.now.year(),
+            self.now.month(),
+            self.now.day(),
+            locale.day_of_week_short(self.now.day_of_week()),
+            self.now.iso_week(),
         )
         .unwrap();
         header.top_left(&s);
 
-        match self.alarm_manager.next_ring(&self.now) {
-            None => header.bottom_left("No alarm"),
-            Some((dow, h, m)) => {
-                s.clear();
-                write!(s, "Alarm: {} {}:{:02}", dow, h, m).unwrap();
-                header.bottom_left(&s);
-            }
-        }
-
-        s.clear();
-        if self.nb_fail_environment != 0 {
-            write!(s, "({}s) ", self.nb_fail_environment).unwrap();
-        }
-        write!(s, "{}hPa", Centi(self.env.pressure as i32)).unwrap();
-        header.bottom_right(&s);
+        let (left, right) = self.header_bottom(alarm_manager);
+        header.bottom_left(&left);
+        header.bottom_right(&right);
 
         s.clear();
         if self.env.humidity != 0 {
@@ -207,26 +395,158 @@ impl Model {
         write!(s, "{}°C", Centi(i32::from(self.env.temperature))).unwrap();
         header.top_right(&s);
     }
+    /// The left and right strings of the bottom line of the header, the
+    /// right one left out when they would overlap.
+    fn header_bottom(&self, alarm_manager: &AlarmManager) -> (String<U128>, String<U128>) {
+        let locale = self.settings.locale;
+        let strings = locale.strings();
+        let mut s: String<U128> = String::new();
+        if let Some(snooze) = alarm_manager.snoozed(self.uptime) {
+            // as `Zz 7:34 2/3`, short enough to leave room for the sun
+            // times in every language
+            let max = alarm_manager.alarms[snooze.id].max_snoozes;
+            let remaining = i64::from(snooze.remaining(self.uptime));
+            let until = self.now.add_seconds(remaining).unwrap_or(self.now);
+            let (h, suffix) = self.settings.time_format.hour(until.hour());
+            let (m, count) = (until.minute(), snooze.count);
+            write!(s, "Zz {}:{:02}{} {}/{}", h, m, suffix, count, max).unwrap();
+        } else if self.fail_storage {
+            s.push_str(strings.not_saved).unwrap();
+        } else if let Some(missed) = alarm_manager.missed {
+            // as `Missed alarm 07:25`, until cancel is pressed
+            let (h, suffix) = self.settings.time_format.hour(missed.at.hour());
+            let m = missed.at.minute();
+            write!(s, "{} {}:{:02}{}", strings.missed_alarm, h, m, suffix).unwrap();
+        } else if let Some(until) = alarm_manager.on_vacation(&self.now) {
+            // as `Vacation to 2026-10-25`
+            let (y, mo, d) = (until.year(), until.month(), until.day());
+            write!(s, "{} {:04}-{:02}-{:02}", strings.vacation_until, y, mo, d).unwrap();
+        } else {
+            let next = alarm_manager.next_alarm(&self.now);
+            let skipped = alarm_manager.next_skipped(&self.now);
+            // the skipped ring, as `Skipped Mo 7:25`, until it is past,
+            // and the label of the next alarm instead of `Alarm`
+            let ring = match (skipped, next) {
+                (Some(skipped), Some((_, next))) if skipped < next => {
+                    Some((strings.skipped, skipped))
+                }
+                (Some(skipped), None) => Some((strings.skipped, skipped)),
+                (_, next) => next.map(|(id, next)| {
+                    let label = alarm_manager.alarms[id].label.as_str();
+                    (
+                        if label.is_empty() {
+                            strings.alarm
+                        } else {
+                            label
+                        },
+                        next,
+                    )
+                }),
+            };
+            match ring {
+                None => s.push_str(strings.no_alarm).unwrap(),
+                Some((label, ring)) => {
+                    // the short day of the week within a week, else the
+                    // date, to keep a label clear of the right string
+                    write!(s, "{} ", label).unwrap();
+                    if ring.date.days() - self.now.date.days() < 7 {
+                        let dow = ring.day_of_week();
+                        write!(s, "{}", locale.day_of_week_short(dow)).unwrap();
+                    } else {
+                        let (y, mo, d) = (ring.year(), ring.month(), ring.day());
+                        write!(s, "{:04}-{:02}-{:02}", y, mo, d).unwrap();
+                    }
+                    let (h, suffix) = self.settings.time_format.hour(ring.hour());
+                    write!(s, " {}:{:02}{}", h, ring.minute(), suffix).unwrap();
+                }
+            }
+        }
+
+        // no room for both, the sun times and the pressure alternate
+        let mut right: String<U128> = String::new();
+        if self.now.second() / 10 % 2 == 1 {
+            self.write_sun_times(&mut right);
+        } else {
+            if self.nb_fail_environment != 0 {
+                write!(right, "({}s) ", self.nb_fail_environment).unwrap();
+            }
+            write!(right, "{}hPa", Centi(self.env.pressure as i32)).unwrap();
+        }
+        if !header::fits(&s, &right) {
+            right.clear();
+        }
+        (s, right)
+    }
+    /// Writes the local sunrise and sunset times, as `Sun 08:42-17:21`, or
+    /// `Sun 08:42a-05:21p` with the 12-hour clock.
+    fn write_sun_times(&self, s: &mut String<U128>) {
+        let location = self.settings.location;
+        let (lat, lon) = (location.latitude_degrees(), location.longitude_degrees());
+        let times = SunTimes::new(&self.now, lat, lon);
+        let local = |dt: Option<datetime::DateTime>| self.tz.to_local(dt?.timestamp());
+        let (sunrise, sunset) = match times {
+            Some(times) => (local(times.sunrise), local(times.sunset)),
+            None => (None, None),
+        };
+        write!(s, "{} ", self.settings.locale.strings().sun).unwrap();
+        let time_format = self.settings.time_format;
+        let write_time = |s: &mut String<U128>, dt: Option<datetime::DateTime>| match dt {
+            None => write!(s, "--:--").unwrap(),
+            Some(dt) => {
+                let (hour, suffix) = time_format.hour(dt.hour());
+                // a compact `a` or `p` to keep the line short
+                let suffix = match suffix.trim_start() {
+                    "AM" => "a",
+                    "PM" => "p",
+                    _ => "",
+                };
+                write!(s, "{:02}:{:02}{}", hour, dt.minute(), suffix).unwrap();
+            }
+        };
+        write_time(s, sunrise);
+        s.push('-').unwrap();
+        write_time(s, sunset);
+    }
     fn render_clock(&self, display: &mut Display2in9) {
-        let mut seven = seven_segments::SevenSegments::new(display, 0, 18);
+        // the secondary date has its own line below the header, the
+        // digits are shorter to leave room for it
+        let calendar = self.settings.calendar;
+        let (y, height) = if calendar == SecondaryCalendar::None {
+            (18, 90)
+        } else {
+            (34, 74)
+        };
+        let mut seven = seven_segments::SevenSegments::new(display, 0, y, height);
 
-        if self.now.hour >= 10 {
-            seven.digit(self.now.hour / 10);
+        let (hour, suffix) = self.settings.time_format.hour(self.now.hour());
+        if hour >= 10 {
+            seven.digit(hour / 10);
         } else {
             seven.digit_space();
         }
-        seven.digit(self.now.hour % 10);
-        if self.now.sec % 2 == 0 {
+        seven.digit(hour % 10);
+        if self.now.second() % 2 == 0 {
             seven.colon();
         } else {
             seven.colon_space();
         }
-        seven.digit(self.now.min / 10);
-        seven.digit(self.now.min % 10);
+        seven.digit(self.now.minute() / 10);
+        seven.digit(self.now.minute() % 10);
 
         let display = seven.into_display();
+        if calendar != SecondaryCalendar::None {
+            let mut s: String<U64> = String::new();
+            calendar.write_date(&self.now.date, &mut s).unwrap();
+            display.draw(
+                Font8x16::render_str(&s)
+                    .with_stroke(Some(Color::Black))
+                    .with_fill(Some(Color::White))
+                    .translate(Coord::new(0, 17))
+                    .into_iter(),
+            );
+        }
         let mut s: String<U4> = String::new();
-        write!(s, ":{:02}", self.now.sec).unwrap();
+        write!(s, ":{:02}", self.now.second()).unwrap();
         display.draw(
             Font8x16::render_str(&s)
                 .with_stroke(Some(Color::Black))
@@ -234,34 +554,195 @@ impl Model {
                 .translate(Coord::new(296 - 3 * 8, 17))
                 .into_iter(),
         );
+        // the moon, between the seconds and AM or PM
+        let utc = datetime::DateTime::from_timestamp(self.tz.to_utc(&self.now)).unwrap_or(self.now);
+        moon::render(
+            display,
+            &Moon::new(&utc),
+            Coord::new(296 - 12, 17 + 16 + 13),
+            11,
+            self.settings.location.latitude < 0,
+        );
+        // AM or PM, below the seconds at the bottom of the digits
+        display.draw(
+            Font8x16::render_str(suffix.trim_start())
+                .with_stroke(Some(Color::Black))
+                .with_fill(Some(Color::White))
+                .translate(Coord::new(296 - 2 * 8, y + height - 16))
+                .into_iter(),
+        );
     }
     fn render_menu(&self, elt: state::MenuElt, display: &mut Display2in9) {
-        menu::render("Menu:", elt.items(), elt as i32, display);
+        let locale = self.settings.locale;
+        menu::render(
+            locale.strings().menu,
+            &elt.items(locale, self.settings.time_format),
+            elt as i32,
+            display,
+        );
     }
     fn render_set_clock(&self, dt: &state::EditDateTime, display: &mut Display2in9) {
+        let locale = self.settings.locale;
+        let (hour, suffix) = self.settings.time_format.hour(dt.datetime.hour());
         let mut title: String<U128> = String::new();
         write!(
             title,
-            "Edit: {:04}-{:02}-{:02} {:02}:{:02}",
-            dt.datetime.year, dt.datetime.month, dt.datetime.day, dt.datetime.hour, dt.datetime.min
+            "{} {:04}-{:02}-{:02} {:02}:{:02}{}",
+            locale.strings().edit,
+            dt.datetime.year(),
+            dt.datetime.month(),
+            dt.datetime.day(),
+            hour,
+            dt.datetime.minute(),
+            suffix
         )
         .unwrap();
-        menu::render(&title, &[dt.as_edit_str()], 0, display);
+        let utc = self.tz.to_utc(&dt.datetime);
+        write!(title, " {}", self.tz.name(utc)).unwrap();
+        menu::render(&title, &[dt.as_edit_str(locale)], 0, display);
     }
-    fn render_manage_alarms(&self, i: usize, display: &mut Display2in9) {
-        let v: Vec<_, U5> = self
-            .alarm_manager
+    fn render_manage_alarms(
+        &self,
+        alarm_manager: &AlarmManager,
+        i: usize,
+        display: &mut Display2in9,
+    ) {
+        let v: Vec<_, MaxAlarms> = alarm_manager
             .alarms
             .iter()
             .map(|a| {
-                let mut s = String::<U40>::new();
-                write!(s, "{}", a).unwrap();
+                // as `Work: On  07:25 Mo Tu`
+                let mut s = String::<U64>::new();
+                let settings = &self.settings;
+                let summary = a.localized(settings.locale, settings.time_format);
+                if !a.label.is_empty() {
+                    write!(s, "{}: ", a.label).unwrap();
+                }
+                write!(s, "{}", summary).unwrap();
                 s
             })
             .collect();
-        let v: Vec<&str, U5> = v.iter().map(|s| s.as_str()).collect();
-        menu::render("Select alarm:", &v, i as i32, display);
+        let strings = self.settings.locale.strings();
+        let mut v: Vec<&str, MaxAlarms> = v.iter().map(|s| s.as_str()).collect();
+        // ignored when there is no room left
+        let _ = v.push(strings.new_alarm);
+        let title = strings.select_alarm;
+        menu::render(title, &v, i as i32, display);
+    }
+    fn render_set_location(&self, edit: &state::EditLocation, display: &mut Display2in9) {
+        let locale = self.settings.locale;
+        let mut title: String<U64> = String::new();
+        write!(title, "{} {}", locale.strings().edit, edit.location).unwrap();
+        menu::render(&title, &[&edit.as_edit_str(locale)], 0, display);
+    }
+    fn render_set_locale(&self, locale: Locale, display: &mut Display2in9) {
+        let mut names: Vec<&str, U4> = Vec::new();
+        let mut l = Locale::default();
+        for _ in 0..4 {
+            names.push(l.name()).unwrap();
+            l = l.next();
+        }
+        let title = locale.strings().language;
+        menu::render(title, &names, locale.as_u8() as i32, display);
+    }
+    fn render_set_calendar(&self, calendar: SecondaryCalendar, display: &mut Display2in9) {
+        let locale = self.settings.locale;
+        let mut names: Vec<&str, U4> = Vec::new();
+        let mut c = SecondaryCalendar::default();
+        for _ in 0..4 {
+            names.push(c.name(locale)).unwrap();
+            c = c.next();
+        }
+        let title = locale.strings().calendar;
+        menu::render(title, &names, calendar.as_u8() as i32, display);
+    }
+    fn render_set_holidays(&self, holidays: Holidays, display: &mut Display2in9) {
+        let locale = self.settings.locale;
+        let mut names: Vec<&str, U8> = Vec::new();
+        let mut h = Holidays::None;
+        for _ in 0..5 {
+            names.push(h.name(locale)).unwrap();
+            h = h.next();
+        }
+        let title = locale.strings().holidays;
+        menu::render(title, &names, holidays.as_u8() as i32, display);
     }
+    fn render_set_grace(&self, mins: u8, display: &mut Display2in9) {
+        let mut s: String<U8> = String::new();
+        write!(s, "{} min", mins).unwrap();
+        let title = self.settings.locale.strings().grace_period;
+        menu::render(title, &[&s], 0, display);
+    }
+    fn render_set_vacation(&self, until: Option<datetime::Date>, display: &mut Display2in9) {
+        let strings = self.settings.locale.strings();
+        let mut s: String<U40> = String::new();
+        match until {
+            Some(until) => write!(
+                s,
+                "{} {:04}-{:02}-{:02}",
+                strings.vacation_until,
+                until.year(),
+                until.month(),
+                until.day()
+            )
+            .unwrap(),
+            None => s.push_str(strings.no_vacation).unwrap(),
+        }
+        menu::render(strings.vacation, &[&s], 0, display);
+    }
+    fn render_ringing(&self, alarm_manager: &AlarmManager, id: usize, display: &mut Display2in9) {
+        let settings = &self.settings;
+        let strings = settings.locale.strings();
+        // removed while ringing
+        let alarm = match alarm_manager.alarms.get(id) {
+            Some(alarm) => alarm,
+            None => return,
+        };
+        // the time of the day, for a cycle, and the label instead of
+        // `Alarm`
+        let time = alarm.time_on(self.now.date);
+        let (hour, suffix) = settings.time_format.hour(time.hour());
+        let name = if alarm.label.is_empty() {
+            strings.alarm
+        } else {
+            alarm.label.as_str()
+        };
+        let mut title: String<U64> = String::new();
+        write!(title, "{} {:02}:{:02}{}", name, hour, time.minute(), suffix).unwrap();
+        let mut label: String<U40> = String::new();
+        write!(
+            label,
+            "{}",
+            alarm.localized(settings.locale, settings.time_format)
+        )
+        .unwrap();
+
+        // as `OK: snooze 9 min (1/3)`, if it can still be snoozed
+        let count = match alarm_manager.snooze {
+            Some(snooze) if snooze.id == id => snooze.count,
+            _ => 0,
+        };
+        let mut snooze: String<U64> = String::new();
+        if count < alarm.max_snoozes {
+            write!(
+                snooze,
+                "{} {} min ({}/{})",
+                strings.snooze_hint,
+                alarm.snooze_mins,
+                count + 1,
+                alarm.max_snoozes
+            )
+            .unwrap();
+        }
+        let lines = [label.as_str(), "", snooze.as_str(), strings.stop_hint];
+        menu::render_lines(&title, &lines, display);
+    }
+}
+
+/// The alarms and the new alarm entry, if there is room left.
+fn nb_manage_alarms(manager: &AlarmManager) -> usize {
+    let alarms = &manager.alarms;
+    alarms.len() + (alarms.len() < alarms.capacity()) as usize
 }
 
 struct Centi(i32);
@@ -297,9 +778,75 @@ impl Default for Environment {
 
 #[cfg(test)]
 mod test {
-    use super::Centi;
+    use super::{header, state::Screen, Centi, Cmd, Model, Msg};
+    use crate::alarm::{Alarm, AlarmManager, Missed, Mode, Recurrence, Snooze, MAX_MAX_SNOOZES};
+    use crate::datetime::{Date, DateTime, Time, TimeZone};
+    use crate::locale::{Locale, TimeFormat};
+    use crate::settings::Settings;
+    use heapless::{consts::U4, Vec};
     use std::string::ToString;
 
+    // the entries of the menu of a weekly alarm
+    const SET_RECURRENCE: usize = 2;
+    const SET_LABEL: usize = 3;
+    const SKIP_NEXT: usize = 7;
+
+    fn model() -> Model {
+        let tz = TimeZone::parse("CET-1CEST,M3.5.0,M10.5.0/3").unwrap();
+        Model::init(tz, Settings::default())
+    }
+
+    /// Sends the buttons, and returns the commands of the last one.
+    fn press(
+        model: &mut Model,
+        alarm_manager: &AlarmManager,
+        buttons: impl IntoIterator<Item = Msg>,
+    ) -> Vec<Cmd, U4> {
+        let mut cmds = Vec::new();
+        for msg in buttons {
+            cmds = model.update(msg, alarm_manager);
+        }
+        cmds
+    }
+
+    fn ok() -> impl Iterator<Item = Msg> {
+        core::iter::once(Msg::ButtonOk)
+    }
+
+    fn plus(n: usize) -> impl Iterator<Item = Msg> {
+        core::iter::repeat_with(|| Msg::ButtonPlus).take(n)
+    }
+
+    fn minus(n: usize) -> impl Iterator<Item = Msg> {
+        core::iter::repeat_with(|| Msg::ButtonMinus).take(n)
+    }
+
+    /// From the clock, the list of the alarms.
+    fn open_alarms() -> impl Iterator<Item = Msg> {
+        ok().chain(plus(2)).chain(ok())
+    }
+
+    /// From the clock, the menu of the alarm `id`, or of a new alarm
+    /// after the last one.
+    fn edit_alarm(id: usize) -> impl Iterator<Item = Msg> {
+        open_alarms().chain(plus(id)).chain(ok())
+    }
+
+    /// From the first entry of a menu.
+    fn select(entry: usize) -> impl Iterator<Item = Msg> {
+        plus(entry).chain(ok())
+    }
+
+    /// From the `entry` of the menu of an alarm, back to the clock.
+    fn save_and_quit(entry: usize) -> impl Iterator<Item = Msg> {
+        minus(entry + 1).chain(ok())
+    }
+
+    /// From the clock, the setting of the end of the vacation.
+    fn open_vacation() -> impl Iterator<Item = Msg> {
+        ok().chain(minus(1)).chain(ok())
+    }
+
     #[test]
     fn centi() {
         assert_eq!(Centi(core::i32::MAX).to_string(), "21474836.47");
@@ -321,4 +868,436 @@ mod test {
 
         assert_eq!(Centi(core::i32::MIN).to_string(), "-21474836.48");
     }
+
+    #[test]
+    fn input_timeout() {
+        let mut model = model();
+        let alarm_manager = AlarmManager::default();
+        model.update(Msg::DateTime(DateTime::new(1_587_720_000)), &alarm_manager);
+        model.update(Msg::Uptime(100), &alarm_manager);
+        model.update(Msg::ButtonOk, &alarm_manager);
+        assert!(model.screen != Screen::Clock);
+        // the clock set back does not change the timeout
+        model.update(Msg::DateTime(DateTime::new(1_587_710_000)), &alarm_manager);
+        model.update(Msg::Uptime(700), &alarm_manager);
+        assert!(model.screen != Screen::Clock);
+        model.update(Msg::Uptime(701), &alarm_manager);
+        assert!(model.screen == Screen::Clock);
+    }
+
+    #[test]
+    fn sun_times() {
+        let mut model = model();
+        let alarm_manager = AlarmManager::default();
+        model.update(Msg::DateTime(DateTime::new(1_587_720_000)), &alarm_manager);
+        let mut s = heapless::String::new();
+        model.write_sun_times(&mut s);
+        assert!(!s.contains('a') && !s.contains('p'), "{}", s);
+
+        model.settings.time_format = TimeFormat::TwelveHour;
+        let mut s = heapless::String::new();
+        model.write_sun_times(&mut s);
+        assert!(s.contains("a-") && s.ends_with('p'), "{}", s);
+    }
+
+    /// All the locales, from English.
+    fn locales() -> impl Iterator<Item = Locale> {
+        let first = Locale::English;
+        core::iter::successors(Some(first), move |l| Some(l.next()).filter(|&l| l != first))
+    }
+
+    #[test]
+    fn header_snooze() {
+        let mut model = model();
+        let mut alarm_manager = AlarmManager::default();
+        let mut alarm = Alarm::default();
+        alarm.max_snoozes = MAX_MAX_SNOOZES;
+        alarm_manager.add(alarm).unwrap();
+        alarm_manager.snooze = Some(Snooze {
+            id: 0,
+            until: 100 + 30 * 60,
+            count: MAX_MAX_SNOOZES,
+        });
+        model.update(Msg::Uptime(100), &alarm_manager);
+        // the sun times, the longest right string
+        let now = DateTime {
+            date: Date::new(2026, 10, 18).unwrap(),
+            time: Time::new(12, 4, 10).unwrap(),
+        };
+        model.update(Msg::DateTime(now), &alarm_manager);
+        for locale in locales() {
+            for &time_format in &[TimeFormat::TwentyFourHour, TimeFormat::TwelveHour] {
+                model.settings.locale = locale;
+                model.settings.time_format = time_format;
+                let (left, right) = model.header_bottom(&alarm_manager);
+                assert!(left.starts_with("Zz 12:34"), "{}", left);
+                assert!(!right.is_empty(), "{} {}", left, right);
+                assert!(header::fits(&left, &right), "{} {}", left, right);
+            }
+        }
+        let (left, _) = model.header_bottom(&alarm_manager);
+        assert_eq!(left, "Zz 12:34 PM 7/7");
+    }
+
+    #[test]
+    fn header_label() {
+        let mut model = model();
+        let mut alarm_manager = AlarmManager::default();
+        let mut alarm = Alarm::default();
+        alarm.is_enable = true;
+        alarm.mode = Mode::WEDNESDAY;
+        alarm.label = "Kids school!".into();
+        alarm_manager.add(alarm).unwrap();
+        model.env.pressure = 101_325;
+        // a Sunday, the pressure then the sun times
+        for &second in &[20, 10] {
+            let now = DateTime {
+                date: Date::new(2026, 10, 18).unwrap(),
+                time: Time::new(9, 0, second).unwrap(),
+            };
+            model.update(Msg::DateTime(now), &alarm_manager);
+            for locale in locales() {
+                for &time_format in &[TimeFormat::TwentyFourHour, TimeFormat::TwelveHour] {
+                    model.settings.locale = locale;
+                    model.settings.time_format = time_format;
+                    let (left, right) = model.header_bottom(&alarm_manager);
+                    assert!(left.starts_with("Kids school! "), "{}", left);
+                    assert!(header::fits(&left, &right), "{} {}", left, right);
+                }
+            }
+        }
+        model.settings.locale = Locale::English;
+        let (left, _) = model.header_bottom(&alarm_manager);
+        assert_eq!(left, "Kids school! We 12:00 PM");
+        let now = DateTime {
+            date: Date::new(2026, 10, 18).unwrap(),
+            time: Time::new(9, 0, 20).unwrap(),
+        };
+        model.update(Msg::DateTime(now), &alarm_manager);
+        let (_, right) = model.header_bottom(&alarm_manager);
+        assert_eq!(right, "1013.25hPa");
+    }
+
+    #[test]
+    fn header_vacation() {
+        let mut model = model();
+        let mut alarm_manager = AlarmManager::default();
+        alarm_manager.vacation_until = Some(Date::new(2026, 10, 25).unwrap());
+        model.env.pressure = 101_325;
+        for locale in locales() {
+            model.settings.locale = locale;
+            // the sun times, then the pressure
+            for &second in &[10, 20] {
+                let now = DateTime {
+                    date: Date::new(2026, 10, 18).unwrap(),
+                    time: Time::new(9, 0, second).unwrap(),
+                };
+                model.update(Msg::DateTime(now), &alarm_manager);
+                let (left, right) = model.header_bottom(&alarm_manager);
+                assert!(left.starts_with(locale.strings().vacation_until));
+                assert!(header::fits(&left, &right), "{} {}", left, right);
+            }
+        }
+        model.settings.locale = Locale::English;
+        let (left, right) = model.header_bottom(&alarm_manager);
+        assert_eq!(left, "Vacation to 2026-10-25");
+        assert_eq!(right, "1013.25hPa");
+    }
+
+    #[test]
+    fn ringing() {
+        let mut model = model();
+        let alarm_manager = AlarmManager::default();
+        model.update(Msg::Uptime(100), &alarm_manager);
+        model.update(Msg::ButtonOk, &alarm_manager);
+        model.update(Msg::AlarmRinging(2), &alarm_manager);
+        assert!(model.screen == Screen::Ringing(2));
+        // no timeout while ringing
+        model.update(Msg::Uptime(2000), &alarm_manager);
+        assert!(model.screen == Screen::Ringing(2));
+        model.update(Msg::ButtonPlus, &alarm_manager);
+        assert!(model.screen == Screen::Ringing(2));
+
+        let cmds = model.update(Msg::ButtonOk, &alarm_manager);
+        assert!(model.screen == Screen::Clock);
+        match &cmds[..] {
+            [Cmd::StopSound, Cmd::SnoozeAlarm(_), Cmd::FullUpdate] => {}
+            cmds => panic!("unexpected {:?}", cmds),
+        }
+
+        model.update(Msg::AlarmRinging(0), &alarm_manager);
+        let cmds = model.update(Msg::ButtonCancel, &alarm_manager);
+        assert!(model.screen == Screen::Clock);
+        match &cmds[..] {
+            [Cmd::StopSound, Cmd::DismissAlarm, Cmd::FullUpdate] => {}
+            cmds => panic!("unexpected {:?}", cmds),
+        }
+
+        // the sound ended by itself
+        model.update(Msg::AlarmRinging(1), &alarm_manager);
+        let cmds = model.update(Msg::SoundEnded, &alarm_manager);
+        assert!(model.screen == Screen::Clock);
+        match &cmds[..] {
+            [Cmd::DismissAlarm, Cmd::FullUpdate] => {}
+            cmds => panic!("unexpected {:?}", cmds),
+        }
+        // no snooze for the alarm no longer ringing
+        assert!(model.update(Msg::ButtonOk, &alarm_manager).is_empty());
+        assert!(model.update(Msg::SoundEnded, &alarm_manager).is_empty());
+    }
+
+    #[test]
+    fn missed_alarm() {
+        let mut model = model();
+        let mut alarm_manager = AlarmManager::default();
+        assert!(model
+            .update(Msg::ButtonCancel, &alarm_manager)
+            .iter()
+            .all(|cmd| match cmd {
+                Cmd::ClearMissed => false,
+                _ => true,
+            }));
+        alarm_manager.missed = Some(Missed {
+            id: 0,
+            at: DateTime::new(1_587_720_000),
+        });
+        let cmds = model.update(Msg::ButtonCancel, &alarm_manager);
+        assert!(model.screen == Screen::Clock);
+        match &cmds[..] {
+            [Cmd::ClearMissed, Cmd::FullUpdate] => {}
+            cmds => panic!("unexpected {:?}", cmds),
+        }
+    }
+
+    #[test]
+    fn fail_storage() {
+        let mut model = model();
+        let alarm_manager = AlarmManager::default();
+        model.update(Msg::FailStorage, &alarm_manager);
+        assert!(model.fail_storage);
+        // still shown after a visit of the menu
+        model.update(Msg::ButtonOk, &alarm_manager);
+        model.update(Msg::ButtonCancel, &alarm_manager);
+        assert!(model.screen == Screen::Clock);
+        assert!(model.fail_storage);
+        model.update(Msg::ButtonCancel, &alarm_manager);
+        assert!(!model.fail_storage);
+    }
+
+    #[test]
+    fn vacation() {
+        let mut model = model();
+        let alarm_manager = AlarmManager::default();
+        let today = Date::new(2026, 10, 18).unwrap();
+        model.update(
+            Msg::DateTime(DateTime {
+                date: today,
+                time: Time::new(9, 0, 0).unwrap(),
+            }),
+            &alarm_manager,
+        );
+        press(&mut model, &alarm_manager, open_vacation());
+        assert!(model.screen == Screen::SetVacation(None));
+        press(&mut model, &alarm_manager, plus(1));
+        assert!(model.screen == Screen::SetVacation(Some(today)));
+        press(&mut model, &alarm_manager, minus(1));
+        assert!(model.screen == Screen::SetVacation(None));
+        let cmds = press(&mut model, &alarm_manager, plus(3).chain(ok()));
+        let until = Date::new(2026, 10, 20).unwrap();
+        match &cmds[..] {
+            [Cmd::SetVacation(Some(date)), Cmd::FullUpdate] => assert_eq!(*date, until),
+            cmds => panic!("unexpected {:?}", cmds),
+        }
+
+        // up to the last day kept in the backup domain
+        let last = Date::new(2149, 6, 6).unwrap();
+        let now = DateTime {
+            date: last,
+            time: Time::new(9, 0, 0).unwrap(),
+        };
+        model.update(Msg::DateTime(now), &alarm_manager);
+        press(&mut model, &alarm_manager, open_vacation().chain(plus(2)));
+        assert!(model.screen == Screen::SetVacation(Some(last)));
+    }
+
+    #[test]
+    fn skip_next() {
+        let mut model = model();
+        let mut alarm_manager = AlarmManager::default();
+        alarm_manager.add(Alarm::default()).unwrap();
+        let buttons = edit_alarm(0)
+            .chain(select(SKIP_NEXT))
+            .chain(save_and_quit(SKIP_NEXT));
+        let cmds = press(&mut model, &alarm_manager, buttons);
+        match &cmds[..] {
+            [Cmd::UpdateAlarm(_, 0), Cmd::SkipAlarm(0, true), Cmd::FullUpdate] => {}
+            cmds => panic!("unexpected {:?}", cmds),
+        }
+    }
+
+    #[test]
+    fn edit_label() {
+        let mut model = model();
+        let mut alarm_manager = AlarmManager::default();
+        let mut alarm = Alarm::default();
+        alarm.label = "Work".into();
+        alarm_manager.add(alarm).unwrap();
+        press(
+            &mut model,
+            &alarm_manager,
+            edit_alarm(0).chain(select(SET_LABEL)),
+        );
+        // `W` to `V` the long way, past the end of the picker, not
+        // reachable in the middle of the label
+        press(
+            &mut model,
+            &alarm_manager,
+            plus(66).chain(ok()).chain(ok()).chain(ok()),
+        );
+        // `k` to the end, removed, then `!` added
+        press(
+            &mut model,
+            &alarm_manager,
+            plus(31).chain(minus(1)).chain(ok()).chain(ok()),
+        );
+        let cmds = press(&mut model, &alarm_manager, save_and_quit(SET_LABEL));
+        match &cmds[..] {
+            [Cmd::UpdateAlarm(alarm, 0), Cmd::FullUpdate] => assert_eq!(alarm.label, "Vor!"),
+            cmds => panic!("unexpected {:?}", cmds),
+        }
+    }
+
+    #[test]
+    fn edit_date_alarm() {
+        let mut model = model();
+        let mut alarm_manager = AlarmManager::default();
+        let mut alarm = Alarm::default();
+        alarm.recurrence = Recurrence::Date(Date::new(2026, 12, 24).unwrap());
+        alarm_manager.add(alarm).unwrap();
+        // past the label, no one time toggle nor days of the week but
+        // the skip of the holidays
+        let skip_holidays = SET_LABEL + 1;
+        let buttons = edit_alarm(0)
+            .chain(select(skip_holidays))
+            .chain(save_and_quit(skip_holidays));
+        let cmds = press(&mut model, &alarm_manager, buttons);
+        match &cmds[..] {
+            [Cmd::UpdateAlarm(alarm, 0), Cmd::FullUpdate] => {
+                assert!(alarm.skip_holidays);
+                assert!(!alarm.mode.contains(Mode::ONE_TIME));
+            }
+            cmds => panic!("unexpected {:?}", cmds),
+        }
+    }
+
+    #[test]
+    fn new_alarm() {
+        let mut model = model();
+        let alarm_manager = AlarmManager::default();
+        press(&mut model, &alarm_manager, open_alarms());
+        // only the new alarm entry
+        assert!(model.screen == Screen::ManageAlarms(0));
+        press(&mut model, &alarm_manager, plus(1));
+        assert!(model.screen == Screen::ManageAlarms(0));
+        press(&mut model, &alarm_manager, ok());
+        let cmds = press(&mut model, &alarm_manager, save_and_quit(0));
+        assert!(model.screen == Screen::Clock);
+        match &cmds[..] {
+            [Cmd::AddAlarm(alarm), Cmd::FullUpdate] => assert!(alarm.is_enable),
+            cmds => panic!("unexpected {:?}", cmds),
+        }
+    }
+
+    #[test]
+    fn edit_recurrence() {
+        let mut model = model();
+        let alarm_manager = AlarmManager::default();
+        let today = Date::new(2026, 1, 31).unwrap();
+        model.update(
+            Msg::DateTime(DateTime {
+                date: today,
+                time: Time::new(9, 0, 0).unwrap(),
+            }),
+            &alarm_manager,
+        );
+        press(
+            &mut model,
+            &alarm_manager,
+            edit_alarm(0).chain(select(SET_RECURRENCE)),
+        );
+        // on a date, a year later, the previous month and day
+        let buttons = std::vec![
+            Msg::ButtonPlus,
+            Msg::ButtonOk,
+            Msg::ButtonPlus,
+            Msg::ButtonOk,
+            Msg::ButtonMinus,
+            Msg::ButtonOk,
+            Msg::ButtonMinus,
+            Msg::ButtonOk,
+        ];
+        press(&mut model, &alarm_manager, buttons);
+        let cmds = press(&mut model, &alarm_manager, save_and_quit(SET_RECURRENCE));
+        let date = Date::new(2027, 12, 30).unwrap();
+        match &cmds[..] {
+            [Cmd::AddAlarm(alarm), Cmd::FullUpdate] => {
+                assert_eq!(alarm.recurrence, Recurrence::Date(date))
+            }
+            cmds => panic!("unexpected {:?}", cmds),
+        }
+    }
+
+    #[test]
+    fn edit_cycle() {
+        let mut model = model();
+        let alarm_manager = AlarmManager::default();
+        let today = Date::new(2026, 10, 5).unwrap();
+        model.update(
+            Msg::DateTime(DateTime {
+                date: today,
+                time: Time::new(9, 0, 0).unwrap(),
+            }),
+            &alarm_manager,
+        );
+        press(
+            &mut model,
+            &alarm_manager,
+            edit_alarm(0).chain(select(SET_RECURRENCE)),
+        );
+        let buttons = std::vec![
+            // a cycle from today
+            Msg::ButtonMinus,
+            Msg::ButtonOk,
+            Msg::ButtonOk,
+            Msg::ButtonOk,
+            Msg::ButtonOk,
+            // 7 days: 2 early, 2 late, 3 off
+            Msg::ButtonMinus,
+            Msg::ButtonOk,
+            Msg::ButtonOk,
+            Msg::ButtonOk,
+            Msg::ButtonPlus,
+            Msg::ButtonOk,
+            Msg::ButtonPlus,
+            Msg::ButtonOk,
+            Msg::ButtonOk,
+            Msg::ButtonOk,
+            Msg::ButtonOk,
+        ];
+        press(&mut model, &alarm_manager, buttons);
+        // the second time at 14:00
+        press(&mut model, &alarm_manager, plus(2).chain(ok()).chain(ok()));
+        let cmds = press(&mut model, &alarm_manager, save_and_quit(SET_RECURRENCE));
+        let alarm = match &cmds[..] {
+            [Cmd::AddAlarm(alarm), Cmd::FullUpdate] => alarm,
+            cmds => panic!("unexpected {:?}", cmds),
+        };
+        let cycle = match alarm.recurrence {
+            Recurrence::Cycle(cycle) => cycle,
+            recurrence => panic!("unexpected {:?}", recurrence),
+        };
+        assert_eq!(cycle.anchor, today);
+        assert_eq!(cycle.second_time, Time::new(14, 0, 0).unwrap());
+        assert_eq!(alarm.to_string(), "On  12:00 Cycle 1122---");
+    }
 }
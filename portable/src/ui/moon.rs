@@ -0,0 +1,46 @@
+use crate::moon::Moon;
+use core::f32::consts::PI;
+use embedded_graphics::coord::Coord;
+use embedded_graphics::prelude::*;
+use embedded_graphics::primitives::{Circle, Line};
+use epd_waveshare::epd2in9::Display2in9;
+use epd_waveshare::prelude::Color;
+use libm::{cosf, roundf, sqrtf};
+
+/// Draws the moon as seen from the northern hemisphere, waxing on the
+/// right, or mirrored for the southern hemisphere. The dark part is
+/// black.
+pub fn render(display: &mut Display2in9, moon: &Moon, center: Coord, radius: i32, south: bool) {
+    display.draw(
+        Circle::new(center, radius as u32)
+            .with_stroke(Some(Color::Black))
+            .into_iter(),
+    );
+    // the terminator is an half ellipse, its abscissa relative to the
+    // half width of the disc
+    let terminator = cosf(2. * PI * moon.lunation);
+    let sign = if south { -1 } else { 1 };
+    for dy in -radius..=radius {
+        let half_width = sqrtf((radius * radius - dy * dy) as f32);
+        let (lit_left, lit_right) = if moon.lunation < 0.5 {
+            (half_width * terminator, half_width)
+        } else {
+            (-half_width, -half_width * terminator)
+        };
+        let mut dark = |x1: f32, x2: f32| {
+            let (x1, x2) = (roundf(x1) as i32, roundf(x2) as i32);
+            if x1 < x2 {
+                display.draw(
+                    Line::new(
+                        Coord::new(center.0 + sign * x1, center.1 + dy),
+                        Coord::new(center.0 + sign * x2, center.1 + dy),
+                    )
+                    .with_stroke(Some(Color::Black))
+                    .into_iter(),
+                );
+            }
+        };
+        dark(-half_width, lit_left);
+        dark(lit_right, half_width);
+    }
+}
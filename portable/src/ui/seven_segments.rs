@@ -14,11 +14,11 @@ pub struct SevenSegments<'a> {
     y: i32,
 }
 impl<'a> SevenSegments<'a> {
-    pub fn new(display: &'a mut Display2in9, x: i32, y: i32) -> Self {
+    pub fn new(display: &'a mut Display2in9, x: i32, y: i32, height: i32) -> Self {
         Self {
             display,
             width: 52,
-            height: 90,
+            height,
             thickness: 12,
             space: 13,
             x,
@@ -1,20 +1,17 @@
 use super::{menu, Cmd};
-use crate::alarm::{Alarm, AlarmManager, Mode};
-use crate::datetime;
+use crate::alarm::{
+    Alarm, AlarmManager, Cycle, MaxLabelLen, Mode, Phase, Recurrence, MAX_CYCLE_DAYS,
+    MAX_MAX_SNOOZES, MAX_SNOOZE_MINS,
+};
+use crate::calendars::SecondaryCalendar;
+use crate::datetime::{self, Date, DayOfWeek, Time};
+use crate::holidays::Holidays;
+use crate::locale::{Locale, TimeFormat};
+use crate::sun::Location;
 use core::fmt::Write;
 use epd_waveshare::epd2in9::Display2in9;
 use heapless::{consts::*, String, Vec};
 
-macro_rules! manage_str {
-    ( $alarm: ident, $d:ident, $m:ident ) => {
-        if $alarm.mode.contains(Mode::$m) {
-            concat!("Remove ", stringify!($d))
-        } else {
-            concat!("Add ", stringify!($d))
-        }
-    };
-}
-
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub enum Screen {
     Clock,
@@ -22,6 +19,16 @@ pub enum Screen {
     SetClock(EditDateTime),
     ManageAlarms(usize),
     ManageAlarm(ManageAlarm),
+    SetLocale(Locale),
+    SetLocation(EditLocation),
+    SetCalendar(SecondaryCalendar),
+    SetHolidays(Holidays),
+    /// The grace period in minutes.
+    SetGrace(u8),
+    /// The last day of the vacation.
+    SetVacation(Option<Date>),
+    /// The alarm ringing, by ID.
+    Ringing(usize),
 }
 
 #[derive(Debug, Copy, Clone, PartialEq, Eq)]
@@ -29,6 +36,13 @@ pub enum MenuElt {
     Clock,
     SetClock,
     ManageAlarms,
+    SetLocale,
+    ToggleTimeFormat,
+    SetLocation,
+    SetCalendar,
+    SetHolidays,
+    SetGrace,
+    SetVacation,
 }
 impl MenuElt {
     pub fn next(&mut self) {
@@ -36,22 +50,51 @@ impl MenuElt {
         *self = match *self {
             Clock => SetClock,
             SetClock => ManageAlarms,
-            ManageAlarms => Clock,
+            ManageAlarms => SetLocale,
+            SetLocale => ToggleTimeFormat,
+            ToggleTimeFormat => SetLocation,
+            SetLocation => SetCalendar,
+            SetCalendar => SetHolidays,
+            SetHolidays => SetGrace,
+            SetGrace => SetVacation,
+            SetVacation => Clock,
         }
     }
     pub fn prev(&mut self) {
         use self::MenuElt::*;
         *self = match *self {
-            Clock => ManageAlarms,
+            Clock => SetVacation,
             SetClock => Clock,
             ManageAlarms => SetClock,
+            SetLocale => ManageAlarms,
+            ToggleTimeFormat => SetLocale,
+            SetLocation => ToggleTimeFormat,
+            SetCalendar => SetLocation,
+            SetHolidays => SetCalendar,
+            SetGrace => SetHolidays,
+            SetVacation => SetGrace,
         }
     }
     pub fn cancel(&mut self) -> Screen {
         Screen::Clock
     }
-    pub fn items(self) -> &'static [&'static str] {
-        &["Main screen", "Set clock", "Manage alarms"]
+    pub fn items(self, locale: Locale, time_format: TimeFormat) -> [&'static str; 10] {
+        let strings = locale.strings();
+        [
+            strings.main_screen,
+            strings.set_clock,
+            strings.manage_alarms,
+            strings.language,
+            match time_format {
+                TimeFormat::TwentyFourHour => strings.use_12_hour,
+                TimeFormat::TwelveHour => strings.use_24_hour,
+            },
+            strings.set_location,
+            strings.calendar,
+            strings.holidays,
+            strings.grace_period,
+            strings.vacation,
+        ]
     }
 }
 
@@ -77,33 +120,54 @@ impl EditDateTime {
     }
     pub fn next(&mut self) {
         use self::EditDateTimeState::*;
+        let (mut year, mut month, mut day, mut hour, mut min) = self.fields();
         match self.state {
+            // limited to what the 32 bit RTC counter can store
             Year => {
-                self.datetime.year += 1;
-                if self.datetime.year > 2105 {
-                    self.datetime.year = 1970;
+                year += 1;
+                if year > 2105 {
+                    year = 1970;
                 }
             }
-            Month => self.datetime.month = self.datetime.month % 12 + 1,
-            Day => self.datetime.day = self.datetime.day % 31 + 1,
-            Hour => self.datetime.hour = (self.datetime.hour + 1) % 24,
-            Min => self.datetime.min = (self.datetime.min + 1) % 60,
+            Month => month = month % 12 + 1,
+            Day => day = day % self.datetime.date.days_in_month() + 1,
+            Hour => hour = (hour + 1) % 24,
+            Min => min = (min + 1) % 60,
         }
+        self.set_fields(year, month, day, hour, min);
     }
     pub fn prev(&mut self) {
         use self::EditDateTimeState::*;
+        let (mut year, mut month, mut day, mut hour, mut min) = self.fields();
         match self.state {
             Year => {
-                self.datetime.year -= 1;
-                if self.datetime.year < 1970 {
-                    self.datetime.year = 2105;
+                year -= 1;
+                if year < 1970 {
+                    year = 2105;
                 }
             }
-            Month => self.datetime.month = (self.datetime.month + 12 - 2) % 12 + 1,
-            Day => self.datetime.day = (self.datetime.day + 31 - 2) % 31 + 1,
-            Hour => self.datetime.hour = (self.datetime.hour + 24 - 1) % 24,
-            Min => self.datetime.min = (self.datetime.min + 60 - 1) % 60,
+            Month => month = (month + 12 - 2) % 12 + 1,
+            Day => {
+                let nb_days = self.datetime.date.days_in_month();
+                day = (day + nb_days - 2) % nb_days + 1;
+            }
+            Hour => hour = (hour + 24 - 1) % 24,
+            Min => min = (min + 60 - 1) % 60,
         }
+        self.set_fields(year, month, day, hour, min);
+    }
+    fn fields(&self) -> (u16, u8, u8, u8, u8) {
+        let dt = &self.datetime;
+        (dt.year(), dt.month(), dt.day(), dt.hour(), dt.minute())
+    }
+    fn set_fields(&mut self, year: u16, month: u8, day: u8, hour: u8, min: u8) {
+        // the day is clamped to the end of the month when the year or
+        // the month changes
+        let nb_days = Date::new(year, month, 1).unwrap().days_in_month();
+        self.datetime = datetime::DateTime {
+            date: Date::new(year, month, core::cmp::min(day, nb_days)).unwrap(),
+            time: Time::new(hour, min, 0).unwrap(),
+        };
     }
     pub fn cancel(&mut self) -> Screen {
         use self::EditDateTimeState::*;
@@ -123,19 +187,107 @@ impl EditDateTime {
             Month => self.state = Day,
             Day => self.state = Hour,
             Hour => self.state = Min,
-            Min => return Some(self.datetime.clone()),
+            Min => return Some(self.datetime),
         }
         None
     }
-    pub fn as_edit_str(&self) -> &'static str {
+    pub fn as_edit_str(&self, locale: Locale) -> &'static str {
         use self::EditDateTimeState::*;
+        let strings = locale.strings();
+        match self.state {
+            Year => strings.set_year,
+            Month => strings.set_month,
+            Day => strings.set_day,
+            Hour => strings.set_hour,
+            Min => strings.set_minute,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EditLocation {
+    pub location: Location,
+    state: EditLocationState,
+}
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum EditLocationState {
+    Latitude,
+    LatitudeTenths,
+    Longitude,
+    LongitudeTenths,
+}
+impl EditLocation {
+    pub fn new(location: Location) -> Self {
+        Self {
+            location,
+            state: EditLocationState::Latitude,
+        }
+    }
+    pub fn next(&mut self) {
+        self.step(1);
+    }
+    pub fn prev(&mut self) {
+        self.step(-1);
+    }
+    /// Moves by one degree or one tenth of degree, in tenths of degree
+    /// as `Location`. The latitude stops at the poles, the longitude
+    /// wraps around the antimeridian.
+    fn step(&mut self, sign: i16) {
+        use self::EditLocationState::*;
+        let (max_lat, max_lon) = (Location::MAX_LATITUDE, Location::MAX_LONGITUDE);
+        let location = &mut self.location;
+        let step = match self.state {
+            Latitude | Longitude => 10 * sign,
+            LatitudeTenths | LongitudeTenths => sign,
+        };
+        match self.state {
+            Latitude | LatitudeTenths => {
+                let latitude = location.latitude + step;
+                location.latitude = core::cmp::max(-max_lat, core::cmp::min(latitude, max_lat));
+            }
+            Longitude | LongitudeTenths => {
+                let mut longitude = location.longitude + step;
+                if longitude > max_lon {
+                    longitude -= 2 * max_lon;
+                } else if longitude < -max_lon {
+                    longitude += 2 * max_lon;
+                }
+                location.longitude = longitude;
+            }
+        }
+    }
+    pub fn cancel(&mut self) -> Screen {
+        use self::EditLocationState::*;
+        match self.state {
+            Latitude => return Screen::Menu(MenuElt::SetLocation),
+            LatitudeTenths => self.state = Latitude,
+            Longitude => self.state = LatitudeTenths,
+            LongitudeTenths => self.state = Longitude,
+        }
+        Screen::SetLocation(self.clone())
+    }
+    pub fn ok(&mut self) -> Option<Location> {
+        use self::EditLocationState::*;
         match self.state {
-            Year => "Set year",
-            Month => "Set month",
-            Day => "Set day",
-            Hour => "Set hour",
-            Min => "Set minute",
+            Latitude => self.state = LatitudeTenths,
+            LatitudeTenths => self.state = Longitude,
+            Longitude => self.state = LongitudeTenths,
+            LongitudeTenths => return Some(self.location),
         }
+        None
+    }
+    pub fn as_edit_str(&self, locale: Locale) -> String<U32> {
+        use self::EditLocationState::*;
+        let strings = locale.strings();
+        let (label, step) = match self.state {
+            Latitude => (strings.set_latitude, "1"),
+            LatitudeTenths => (strings.set_latitude, "0.1"),
+            Longitude => (strings.set_longitude, "1"),
+            LongitudeTenths => (strings.set_longitude, "0.1"),
+        };
+        let mut s = String::new();
+        write!(s, "{} ±{}°", label, step).unwrap();
+        s
     }
 }
 
@@ -143,13 +295,34 @@ impl EditDateTime {
 pub struct ManageAlarm {
     id: usize,
     alarm: Alarm,
+    /// Not yet in the manager, added when saved.
+    is_new: bool,
+    /// Skips the next ring, saved with the alarm.
+    skip_next: bool,
+    was_skipped: bool,
+    /// The default date of the recurrences.
+    today: Date,
     state: ManageAlarmState,
 }
 impl ManageAlarm {
-    pub fn new(manager: &AlarmManager, id: usize) -> Self {
+    /// A new alarm if `id` is past the alarms of `manager`.
+    pub fn new(manager: &AlarmManager, id: usize, today: Date) -> Self {
+        let (alarm, is_new) = match manager.alarms.get(id) {
+            Some(alarm) => (alarm.clone(), false),
+            None => {
+                let mut alarm = Alarm::default();
+                alarm.is_enable = true;
+                (alarm, true)
+            }
+        };
+        let skip_next = manager.is_skipped(id) && !is_new;
         Self {
             id,
-            alarm: manager.alarms[id].clone(),
+            alarm,
+            is_new,
+            skip_next,
+            was_skipped: skip_next,
+            today,
             state: ManageAlarmState::Main(ManageAlarmMainState::ToggleEnable),
         }
     }
@@ -157,13 +330,13 @@ impl ManageAlarm {
         self.state.ok(&self, cmds)
     }
     pub fn next(&mut self) {
-        self.state = self.state.next(&mut self.alarm);
+        self.state = self.state.next(&mut self.alarm, self.today);
     }
     pub fn prev(&mut self) {
-        self.state = self.state.prev(&mut self.alarm);
+        self.state = self.state.prev(&mut self.alarm, self.today);
     }
     pub fn cancel(&mut self) -> Screen {
-        match self.state.cancel() {
+        match self.state.cancel(&self.alarm) {
             None => Screen::ManageAlarms(self.id),
             Some(state) => Screen::ManageAlarm(Self {
                 state,
@@ -171,8 +344,8 @@ impl ManageAlarm {
             }),
         }
     }
-    pub fn render(&self, display: &mut Display2in9) {
-        self.state.render(&self.alarm, display);
+    pub fn render(&self, locale: Locale, time_format: TimeFormat, display: &mut Display2in9) {
+        self.state.render(self, locale, time_format, display);
     }
 }
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -181,6 +354,19 @@ enum ManageAlarmState {
     SetHour,
     SetMin,
     ManageRepeat(ManageAlarmManageRepeatState),
+    SetSnoozeMins,
+    SetMaxSnoozes,
+    SetRecurrence,
+    SetYear,
+    SetMonth,
+    SetDay,
+    SetCycleDays,
+    /// The phase of a day of the cycle.
+    SetPhase(u8),
+    SetSecondHour,
+    SetSecondMin,
+    /// The character of the label at a position.
+    SetLabel(u8),
 }
 impl ManageAlarmState {
     pub fn ok(self, manage: &ManageAlarm, cmds: &mut Vec<Cmd, U4>) -> Screen {
@@ -198,12 +384,62 @@ impl ManageAlarmState {
                 Screen::ManageAlarm(manage)
             }
             ManageRepeat(state) => state.ok(manage),
+            SetSnoozeMins => {
+                let mut manage = manage.clone();
+                manage.state = SetMaxSnoozes;
+                Screen::ManageAlarm(manage)
+            }
+            SetMaxSnoozes => {
+                let mut manage = manage.clone();
+                manage.state = Main(ManageAlarmMainState::SetSnooze);
+                Screen::ManageAlarm(manage)
+            }
+            SetRecurrence | SetYear | SetMonth | SetDay => {
+                let mut manage = manage.clone();
+                manage.state = match (self, manage.alarm.recurrence) {
+                    (SetRecurrence, Recurrence::Date(_)) => SetYear,
+                    (SetRecurrence, Recurrence::Monthly(_)) => SetDay,
+                    (SetRecurrence, Recurrence::Yearly(..)) => SetMonth,
+                    (SetRecurrence, Recurrence::Cycle(_)) => SetYear,
+                    (SetYear, _) => SetMonth,
+                    (SetMonth, _) => SetDay,
+                    (SetDay, Recurrence::Cycle(_)) => SetCycleDays,
+                    _ => Main(ManageAlarmMainState::SetRecurrence),
+                };
+                Screen::ManageAlarm(manage)
+            }
+            SetLabel(pos) => {
+                let mut manage = manage.clone();
+                let (pos, label) = (usize::from(pos), &manage.alarm.label);
+                manage.state = if pos >= label.len() || pos + 1 == label.capacity() {
+                    Main(ManageAlarmMainState::SetLabel)
+                } else {
+                    SetLabel(pos as u8 + 1)
+                };
+                Screen::ManageAlarm(manage)
+            }
+            SetCycleDays | SetPhase(_) | SetSecondHour | SetSecondMin => {
+                let mut manage = manage.clone();
+                let cycle = match manage.alarm.recurrence {
+                    Recurrence::Cycle(cycle) => cycle,
+                    _ => return Screen::ManageAlarm(manage),
+                };
+                let has_second = (0..cycle.nb_days()).any(|d| cycle.phase(d) == Phase::Second);
+                manage.state = match self {
+                    SetCycleDays => SetPhase(0),
+                    SetPhase(day) if day + 1 < cycle.nb_days() => SetPhase(day + 1),
+                    SetPhase(_) if has_second => SetSecondHour,
+                    SetSecondHour => SetSecondMin,
+                    _ => Main(ManageAlarmMainState::SetRecurrence),
+                };
+                Screen::ManageAlarm(manage)
+            }
         }
     }
-    pub fn next(self, alarm: &mut Alarm) -> Self {
+    pub fn next(self, alarm: &mut Alarm, today: Date) -> Self {
         use self::ManageAlarmState::*;
         match self {
-            Main(state) => Main(state.next()),
+            Main(state) => Main(state.next(alarm)),
             SetHour => {
                 let hour = alarm.hour();
                 alarm.set_hour((hour + 1) % 24);
@@ -215,12 +451,38 @@ impl ManageAlarmState {
                 SetMin
             }
             ManageRepeat(state) => ManageRepeat(state.next()),
+            SetSnoozeMins => {
+                alarm.snooze_mins = alarm.snooze_mins % MAX_SNOOZE_MINS + 1;
+                SetSnoozeMins
+            }
+            SetMaxSnoozes => {
+                alarm.max_snoozes = (alarm.max_snoozes + 1) % (MAX_MAX_SNOOZES + 1);
+                SetMaxSnoozes
+            }
+            SetRecurrence => {
+                alarm.recurrence = match alarm.recurrence {
+                    Recurrence::Weekly => Recurrence::Date(today),
+                    Recurrence::Date(_) => Recurrence::Monthly(today.day()),
+                    Recurrence::Monthly(_) => Recurrence::Yearly(today.month(), today.day()),
+                    Recurrence::Yearly(..) => Recurrence::Cycle(Cycle::new(today, alarm.time())),
+                    Recurrence::Cycle(_) => Recurrence::Weekly,
+                };
+                SetRecurrence
+            }
+            SetLabel(pos) => {
+                alarm.label = step_label(&alarm.label, usize::from(pos), 1);
+                SetLabel(pos)
+            }
+            _ => {
+                alarm.recurrence = step_recurrence(alarm.recurrence, self, 1);
+                self
+            }
         }
     }
-    pub fn prev(self, alarm: &mut Alarm) -> Self {
+    pub fn prev(self, alarm: &mut Alarm, today: Date) -> Self {
         use self::ManageAlarmState::*;
         match self {
-            Main(state) => Main(state.prev()),
+            Main(state) => Main(state.prev(alarm)),
             SetHour => {
                 let hour = alarm.hour();
                 alarm.set_hour((hour + 24 - 1) % 24);
@@ -232,61 +494,332 @@ impl ManageAlarmState {
                 SetMin
             }
             ManageRepeat(state) => ManageRepeat(state.prev()),
+            SetSnoozeMins => {
+                alarm.snooze_mins = (alarm.snooze_mins + MAX_SNOOZE_MINS - 2) % MAX_SNOOZE_MINS + 1;
+                SetSnoozeMins
+            }
+            SetMaxSnoozes => {
+                alarm.max_snoozes = (alarm.max_snoozes + MAX_MAX_SNOOZES) % (MAX_MAX_SNOOZES + 1);
+                SetMaxSnoozes
+            }
+            SetRecurrence => {
+                alarm.recurrence = match alarm.recurrence {
+                    Recurrence::Weekly => Recurrence::Cycle(Cycle::new(today, alarm.time())),
+                    Recurrence::Date(_) => Recurrence::Weekly,
+                    Recurrence::Monthly(_) => Recurrence::Date(today),
+                    Recurrence::Yearly(..) => Recurrence::Monthly(today.day()),
+                    Recurrence::Cycle(_) => Recurrence::Yearly(today.month(), today.day()),
+                };
+                SetRecurrence
+            }
+            SetLabel(pos) => {
+                alarm.label = step_label(&alarm.label, usize::from(pos), -1);
+                SetLabel(pos)
+            }
+            _ => {
+                alarm.recurrence = step_recurrence(alarm.recurrence, self, -1);
+                self
+            }
         }
     }
-    pub fn cancel(self) -> Option<Self> {
+    pub fn cancel(self, alarm: &Alarm) -> Option<Self> {
         use self::ManageAlarmState::*;
         match self {
             Main(..) => None,
             SetHour => Some(Main(ManageAlarmMainState::SetTime)),
             SetMin => Some(SetHour),
             ManageRepeat(..) => Some(Main(ManageAlarmMainState::ManageRepeat)),
+            SetSnoozeMins => Some(Main(ManageAlarmMainState::SetSnooze)),
+            SetMaxSnoozes => Some(SetSnoozeMins),
+            SetRecurrence => Some(Main(ManageAlarmMainState::SetRecurrence)),
+            SetYear => Some(SetRecurrence),
+            SetMonth => match alarm.recurrence {
+                Recurrence::Date(_) | Recurrence::Cycle(_) => Some(SetYear),
+                _ => Some(SetRecurrence),
+            },
+            SetDay => match alarm.recurrence {
+                Recurrence::Monthly(_) => Some(SetRecurrence),
+                _ => Some(SetMonth),
+            },
+            SetCycleDays => Some(SetDay),
+            SetPhase(0) => Some(SetCycleDays),
+            SetPhase(day) => Some(SetPhase(day - 1)),
+            SetSecondHour => match alarm.recurrence {
+                Recurrence::Cycle(cycle) => Some(SetPhase(cycle.nb_days() - 1)),
+                _ => Some(Main(ManageAlarmMainState::SetRecurrence)),
+            },
+            SetSecondMin => Some(SetSecondHour),
+            SetLabel(0) => Some(Main(ManageAlarmMainState::SetLabel)),
+            SetLabel(pos) => Some(SetLabel(pos - 1)),
         }
     }
-    pub fn render(self, alarm: &Alarm, display: &mut Display2in9) {
+    pub fn render(
+        self,
+        manage: &ManageAlarm,
+        locale: Locale,
+        time_format: TimeFormat,
+        display: &mut Display2in9,
+    ) {
         use self::ManageAlarmState::*;
+        let alarm = &manage.alarm;
 
-        let mut title = String::<U40>::new();
-        write!(title, "Edit: {}", alarm).unwrap();
+        let strings = locale.strings();
+        let mut title = String::<U64>::new();
+        let summary = alarm.localized(locale, time_format);
+        write!(title, "{} {}", strings.edit, summary).unwrap();
         match self {
             Main(state) => {
                 let menu = [
-                    if alarm.is_enable { "Disable" } else { "Enable" },
-                    "Set Time",
+                    if alarm.is_enable {
+                        strings.disable
+                    } else {
+                        strings.enable
+                    },
+                    strings.set_time,
+                    strings.set_recurrence,
+                    strings.set_label,
                     if alarm.mode.contains(Mode::ONE_TIME) {
-                        "Repeat"
+                        strings.repeat
+                    } else {
+                        strings.set_one_time
+                    },
+                    strings.manage_repeat,
+                    if alarm.skip_holidays {
+                        strings.ring_on_holidays
+                    } else {
+                        strings.skip_holidays
+                    },
+                    if manage.skip_next {
+                        strings.ring_next
                     } else {
-                        "One time"
+                        strings.skip_next
                     },
-                    "Manage repeat",
-                    "Save and quit",
+                    strings.set_snooze,
+                    strings.remove_alarm,
+                    strings.save_and_quit,
                 ];
-                menu::render(&title, &menu, state as i32, display);
+                let mut items: Vec<&str, U16> = Vec::new();
+                let mut selected = 0;
+                for (s, &item) in ManageAlarmMainState::all().zip(menu.iter()) {
+                    if s == state {
+                        selected = items.len() as i32;
+                    }
+                    if s.is_shown(alarm) {
+                        items.push(item).unwrap();
+                    }
+                }
+                menu::render(&title, &items, selected, display);
             }
-            SetHour => menu::render(&title, &["Set hour"], 0, display),
-            SetMin => menu::render(&title, &["Set minute"], 0, display),
-            ManageRepeat(state) => {
-                let menu = [
-                    manage_str!(alarm, Monday, MONDAY),
-                    manage_str!(alarm, Tuesday, TUESDAY),
-                    manage_str!(alarm, Wednesday, WEDNESDAY),
-                    manage_str!(alarm, Thursday, THURSDAY),
-                    manage_str!(alarm, Friday, FRIDAY),
-                    manage_str!(alarm, Saturday, SATURDAY),
-                    manage_str!(alarm, Sunday, SUNDAY),
-                    "Back",
+            SetHour => menu::render(&title, &[strings.set_hour], 0, display),
+            SetMin => menu::render(&title, &[strings.set_minute], 0, display),
+            SetRecurrence => {
+                let kinds = [
+                    strings.weekly,
+                    strings.on_date,
+                    strings.monthly,
+                    strings.yearly,
+                    strings.cycle,
                 ];
+                let selected = match alarm.recurrence {
+                    Recurrence::Weekly => 0,
+                    Recurrence::Date(_) => 1,
+                    Recurrence::Monthly(_) => 2,
+                    Recurrence::Yearly(..) => 3,
+                    Recurrence::Cycle(_) => 4,
+                };
+                menu::render(&title, &kinds, selected, display);
+            }
+            SetYear => menu::render(&title, &[strings.set_year], 0, display),
+            SetMonth => menu::render(&title, &[strings.set_month], 0, display),
+            SetDay => menu::render(&title, &[strings.set_day], 0, display),
+            SetLabel(pos) => {
+                // as `Label: Work_`, the cursor below
+                let mut s = String::<U40>::new();
+                write!(s, "{} {}", strings.label, alarm.label).unwrap();
+                if usize::from(pos) >= alarm.label.len() {
+                    s.push('_').unwrap();
+                }
+                let mut cursor = String::<U40>::new();
+                for _ in 0..strings.label.chars().count() + 1 + usize::from(pos) {
+                    cursor.push(' ').unwrap();
+                }
+                cursor.push('^').unwrap();
+                menu::render(&title, &[&s, &cursor], 0, display);
+            }
+            SetCycleDays | SetPhase(_) | SetSecondHour | SetSecondMin => {
+                let cycle = match alarm.recurrence {
+                    Recurrence::Cycle(cycle) => cycle,
+                    _ => return,
+                };
+                let hour_min = |s: &mut String<U40>, time: Time| {
+                    let (hour, suffix) = time_format.hour(time.hour());
+                    write!(s, "{:02}:{:02}{}", hour, time.minute(), suffix).unwrap();
+                };
+                match self {
+                    SetPhase(day) => {
+                        let mut items: Vec<String<U40>, U16> = Vec::new();
+                        for d in 0..cycle.nb_days() {
+                            let mut s = String::new();
+                            write!(s, "{} {}: ", strings.day, d + 1).unwrap();
+                            match cycle.phase(d) {
+                                Phase::Off => s.push_str(strings.off).unwrap(),
+                                Phase::First => hour_min(&mut s, alarm.time()),
+                                Phase::Second => hour_min(&mut s, cycle.second_time),
+                            }
+                            items.push(s).unwrap();
+                        }
+                        let menu: Vec<&str, U16> = items.iter().map(|s| s.as_str()).collect();
+                        menu::render(&title, &menu, i32::from(day), display);
+                    }
+                    SetCycleDays => {
+                        let mut s = String::<U40>::new();
+                        write!(s, "{} {}", strings.cycle_days, cycle.nb_days()).unwrap();
+                        menu::render(&title, &[&s], 0, display);
+                    }
+                    _ => {
+                        let mut s = String::<U40>::new();
+                        write!(s, "{} ", strings.second_time).unwrap();
+                        hour_min(&mut s, cycle.second_time);
+                        let set = match self {
+                            SetSecondHour => strings.set_hour,
+                            _ => strings.set_minute,
+                        };
+                        menu::render(&title, &[&s, set], 1, display);
+                    }
+                }
+            }
+            SetSnoozeMins => {
+                let mut s = String::<U40>::new();
+                write!(s, "{} {} min", strings.snooze_duration, alarm.snooze_mins).unwrap();
+                menu::render(&title, &[&s], 0, display);
+            }
+            SetMaxSnoozes => {
+                let mut s = String::<U40>::new();
+                write!(s, "{} {}", strings.max_snoozes, alarm.max_snoozes).unwrap();
+                menu::render(&title, &[&s], 0, display);
+            }
+            ManageRepeat(state) => {
+                let mut items: Vec<String<U40>, U8> = Vec::new();
+                let mut dow = DayOfWeek::Monday;
+                for _ in 0..7 {
+                    let (prefix, suffix) = if alarm.mode.contains_dow(dow) {
+                        strings.remove_day
+                    } else {
+                        strings.add_day
+                    };
+                    let mut s = String::new();
+                    write!(s, "{}{}{}", prefix, locale.day_of_week(dow), suffix).unwrap();
+                    items.push(s).unwrap();
+                    dow = dow.next();
+                }
+                items.push(String::from(strings.back)).unwrap();
+                let menu: Vec<&str, U8> = items.iter().map(|s| s.as_str()).collect();
                 menu::render(&title, &menu, state as i32, display);
             }
         }
     }
 }
+/// The characters of the labels, in the order of the picker.
+const LABEL_CHARS: &str = "ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789 -.'!";
+
+/// Steps the character at `pos` of `label` by `delta` in the picker. The
+/// end of the label is a step between the last and the first characters,
+/// only at the end so that no character is lost.
+fn step_label(label: &str, pos: usize, delta: i32) -> String<MaxLabelLen> {
+    let nb = LABEL_CHARS.len() as i32 + 1;
+    let index = label[pos..]
+        .chars()
+        .next()
+        .and_then(|c| LABEL_CHARS.find(c))
+        .map_or(0, |i| i as i32 + 1);
+    let mut index = (index + delta).rem_euclid(nb);
+    if index == 0 && pos + 1 < label.len() {
+        index = (index + delta).rem_euclid(nb);
+    }
+    let mut new = String::from(&label[..pos]);
+    if index != 0 {
+        new.push(char::from(LABEL_CHARS.as_bytes()[index as usize - 1]))
+            .unwrap();
+        new.push_str(label.get(pos + 1..).unwrap_or("")).unwrap();
+    }
+    new
+}
+
+/// Adds `delta` to the field of `recurrence` edited in `state`, the day
+/// being clamped to the month and the minutes going by 5.
+fn step_recurrence(recurrence: Recurrence, state: ManageAlarmState, delta: i32) -> Recurrence {
+    use self::ManageAlarmState::*;
+    let wrap =
+        |value: u8, max: u8| ((i32::from(value) - 1 + delta).rem_euclid(i32::from(max)) + 1) as u8;
+    let nb_days =
+        |year: u16, month: u8| Date::new(year, month, 1).map_or(31, |d| d.days_in_month());
+    let step_date = |date: Date| {
+        let (mut year, mut month, mut day) = (date.year(), date.month(), date.day());
+        match state {
+            SetYear => year = (i32::from(year) - 2000 + delta).rem_euclid(100) as u16 + 2000,
+            SetMonth => month = wrap(month, 12),
+            _ => day = wrap(day, nb_days(year, month)),
+        }
+        let day = core::cmp::min(day, nb_days(year, month));
+        Date::new(year, month, day).ok()
+    };
+    match (recurrence, state) {
+        (Recurrence::Date(date), _) => step_date(date).map_or(recurrence, Recurrence::Date),
+        (Recurrence::Cycle(mut cycle), SetYear)
+        | (Recurrence::Cycle(mut cycle), SetMonth)
+        | (Recurrence::Cycle(mut cycle), SetDay) => {
+            cycle.anchor = step_date(cycle.anchor).unwrap_or(cycle.anchor);
+            Recurrence::Cycle(cycle)
+        }
+        (Recurrence::Cycle(mut cycle), SetCycleDays) => {
+            cycle.set_nb_days(wrap(cycle.nb_days(), MAX_CYCLE_DAYS));
+            Recurrence::Cycle(cycle)
+        }
+        (Recurrence::Cycle(mut cycle), SetPhase(day)) => {
+            let phase = cycle.phase(day);
+            let phase = if delta > 0 {
+                phase.next()
+            } else {
+                phase.prev()
+            };
+            cycle.set_phase(day, phase);
+            Recurrence::Cycle(cycle)
+        }
+        (Recurrence::Cycle(mut cycle), SetSecondHour)
+        | (Recurrence::Cycle(mut cycle), SetSecondMin) => {
+            let (mut hour, mut min) = (cycle.second_time.hour(), cycle.second_time.minute());
+            match state {
+                SetSecondHour => hour = (i32::from(hour) + delta).rem_euclid(24) as u8,
+                _ => min = (i32::from(min) + 5 * delta).rem_euclid(60) as u8,
+            }
+            cycle.second_time = Time::new(hour, min, 0).unwrap();
+            Recurrence::Cycle(cycle)
+        }
+        (Recurrence::Monthly(day), SetDay) => Recurrence::Monthly(wrap(day, 31)),
+        // 2000 being a leap year, the 29th of February is allowed
+        (Recurrence::Yearly(month, day), SetMonth) => {
+            let month = wrap(month, 12);
+            Recurrence::Yearly(month, core::cmp::min(day, nb_days(2000, month)))
+        }
+        (Recurrence::Yearly(month, day), SetDay) => {
+            Recurrence::Yearly(month, wrap(day, nb_days(2000, month)))
+        }
+        _ => recurrence,
+    }
+}
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 enum ManageAlarmMainState {
     ToggleEnable,
     SetTime,
+    SetRecurrence,
+    SetLabel,
     ToggleOneTime,
     ManageRepeat,
+    ToggleSkipHolidays,
+    ToggleSkipNext,
+    SetSnooze,
+    Remove,
     Quit,
 }
 impl ManageAlarmMainState {
@@ -303,6 +836,16 @@ impl ManageAlarmMainState {
                 manage.state = ManageAlarmState::SetHour;
                 Screen::ManageAlarm(manage)
             }
+            SetRecurrence => {
+                let mut manage = manage.clone();
+                manage.state = ManageAlarmState::SetRecurrence;
+                Screen::ManageAlarm(manage)
+            }
+            SetLabel => {
+                let mut manage = manage.clone();
+                manage.state = ManageAlarmState::SetLabel(0);
+                Screen::ManageAlarm(manage)
+            }
             ToggleOneTime => {
                 let mut manage = manage.clone();
                 manage.alarm.mode.toggle(Mode::ONE_TIME);
@@ -314,31 +857,103 @@ impl ManageAlarmMainState {
                 manage.state = ManageAlarmState::ManageRepeat(Monday);
                 Screen::ManageAlarm(manage)
             }
+            ToggleSkipHolidays => {
+                let mut manage = manage.clone();
+                manage.alarm.skip_holidays = !manage.alarm.skip_holidays;
+                Screen::ManageAlarm(manage)
+            }
+            ToggleSkipNext => {
+                let mut manage = manage.clone();
+                manage.skip_next = !manage.skip_next;
+                Screen::ManageAlarm(manage)
+            }
+            SetSnooze => {
+                let mut manage = manage.clone();
+                manage.state = ManageAlarmState::SetSnoozeMins;
+                Screen::ManageAlarm(manage)
+            }
+            Remove => {
+                if !manage.is_new {
+                    cmds.push(Cmd::RemoveAlarm(manage.id)).unwrap();
+                }
+                Screen::ManageAlarms(manage.id)
+            }
             Quit => {
-                cmds.push(Cmd::UpdateAlarm(manage.alarm.clone(), manage.id))
-                    .unwrap();
+                let alarm = manage.alarm.clone();
+                let cmd = if manage.is_new {
+                    Cmd::AddAlarm(alarm)
+                } else {
+                    Cmd::UpdateAlarm(alarm, manage.id)
+                };
+                cmds.push(cmd).unwrap();
+                if manage.skip_next != manage.was_skipped {
+                    cmds.push(Cmd::SkipAlarm(manage.id, manage.skip_next))
+                        .unwrap();
+                }
                 Screen::Clock
             }
         }
     }
-    pub fn next(self) -> Self {
+    /// The entries of the menu, shown or not.
+    fn all() -> impl Iterator<Item = Self> {
+        let first = ManageAlarmMainState::ToggleEnable;
+        core::iter::successors(Some(first), move |&s| {
+            Some(s.following()).filter(|&s| s != first)
+        })
+    }
+    /// The days of the week and the one time mode only apply to the
+    /// weekly alarms.
+    fn is_shown(self, alarm: &Alarm) -> bool {
+        use self::ManageAlarmMainState::*;
+        match self {
+            ToggleOneTime | ManageRepeat => alarm.recurrence == Recurrence::Weekly,
+            _ => true,
+        }
+    }
+    pub fn next(self, alarm: &Alarm) -> Self {
+        let mut state = self.following();
+        while !state.is_shown(alarm) {
+            state = state.following();
+        }
+        state
+    }
+    pub fn prev(self, alarm: &Alarm) -> Self {
+        let mut state = self.preceding();
+        while !state.is_shown(alarm) {
+            state = state.preceding();
+        }
+        state
+    }
+    fn following(self) -> Self {
         use self::ManageAlarmMainState::*;
         match self {
             ToggleEnable => SetTime,
-            SetTime => ToggleOneTime,
+            SetTime => SetRecurrence,
+            SetRecurrence => SetLabel,
+            SetLabel => ToggleOneTime,
             ToggleOneTime => ManageRepeat,
-            ManageRepeat => Quit,
+            ManageRepeat => ToggleSkipHolidays,
+            ToggleSkipHolidays => ToggleSkipNext,
+            ToggleSkipNext => SetSnooze,
+            SetSnooze => Remove,
+            Remove => Quit,
             Quit => ToggleEnable,
         }
     }
-    pub fn prev(self) -> Self {
+    fn preceding(self) -> Self {
         use self::ManageAlarmMainState::*;
         match self {
             ToggleEnable => Quit,
             SetTime => ToggleEnable,
-            ToggleOneTime => SetTime,
+            SetRecurrence => SetTime,
+            SetLabel => SetRecurrence,
+            ToggleOneTime => SetLabel,
             ManageRepeat => ToggleOneTime,
-            Quit => ManageRepeat,
+            ToggleSkipHolidays => ManageRepeat,
+            ToggleSkipNext => ToggleSkipHolidays,
+            SetSnooze => ToggleSkipNext,
+            Remove => SetSnooze,
+            Quit => Remove,
         }
     }
 }
@@ -0,0 +1,37 @@
+//! The skipped alarms and the vacation, see
+//! `AlarmManager::backup_as_u16s`, kept in the data registers of the
+//! backup domain.
+//!
+//! An older firmware kept the alarms in the 10 data registers. The first
+//! one holds `MARKER` once they are cleared by `reset`, a value that is
+//! not the low half word of an alarm, its minutes being above 59.
+
+use stm32f1xx_hal::backup_domain::BackupDomain;
+
+/// The data registers of the STM32F103C8.
+const NB_REGISTERS: usize = 10;
+const MARKER: u16 = 0xc10c;
+
+/// `None` if the registers are not in use: after a reset of the backup
+/// domain, or holding the alarms of an older firmware.
+pub fn read(bkp: &BackupDomain) -> Option<[u16; 2]> {
+    if bkp.read_data_register_low(0) != MARKER {
+        return None;
+    }
+    Some([bkp.read_data_register_low(1), bkp.read_data_register_low(2)])
+}
+
+pub fn write(bkp: &mut BackupDomain, data: [u16; 2]) {
+    for (i, &half_word) in data.iter().enumerate() {
+        bkp.write_data_register_low(i + 1, half_word);
+    }
+}
+
+/// Clears the registers, no alarm skipped and no vacation, and marks
+/// them as in use.
+pub fn reset(bkp: &mut BackupDomain) {
+    for i in 1..NB_REGISTERS {
+        bkp.write_data_register_low(i, 0);
+    }
+    bkp.write_data_register_low(0, MARKER);
+}
@@ -6,14 +6,23 @@ extern crate panic_semihosting;
 
 use embedded_hal::digital::v1_compat::{OldInputPin, OldOutputPin};
 use epd_waveshare::prelude::*;
-use portable::datetime::DateTime;
-use portable::{alarm, button, datetime, ui};
+use portable::datetime::{Date, DateTime, Time, TimeZone};
+use portable::settings::Settings;
+use portable::{alarm, button, calibration, nmea, radio, ui};
 use pwm_speaker::songs::SO_WHAT;
 use rtfm::app;
 use stm32f1xx_hal::prelude::*;
-use stm32f1xx_hal::{delay, gpio, i2c, rtc, spi, stm32, timer};
+use stm32f1xx_hal::{delay, gpio, i2c, rtc, serial, spi, stm32, timer};
 
+mod alarm_backup;
+mod rtc_calibration;
 mod sound;
+mod storage;
+
+// POSIX TZ string of the local time zone, the RTC counts UTC seconds
+const TIME_ZONE: &str = "CET-1CEST,M3.5.0,M10.5.0/3";
+// time signal received on PB8
+const RADIO_PROTOCOL: radio::Protocol = radio::Protocol::Dcf77;
 
 type I2C = i2c::BlockingI2c<
     stm32::I2C1,
@@ -26,6 +35,8 @@ type Button0Pin = gpio::gpioa::PA6<gpio::Input<gpio::PullUp>>;
 type Button1Pin = gpio::gpioa::PA7<gpio::Input<gpio::PullUp>>;
 type Button2Pin = gpio::gpiob::PB0<gpio::Input<gpio::PullUp>>;
 type Button3Pin = gpio::gpiob::PB1<gpio::Input<gpio::PullUp>>;
+// output of the time signal receiver, high when the carrier is reduced
+type RadioPin = gpio::gpiob::PB8<gpio::Input<gpio::Floating>>;
 type Spi = spi::Spi<
     stm32::SPI2,
     stm32f1xx_hal::spi::Spi2NoRemap,
@@ -47,6 +58,7 @@ type EPaperDisplay = epd_waveshare::epd2in9::EPD2in9<
 const APP: () = {
     struct Resources {
         rtc_dev: rtc::Rtc,
+        tz: TimeZone,
         bme280: bme280::BME280<I2C, delay::Delay>,
         alarm_manager: alarm::AlarmManager,
         sound: sound::Sound,
@@ -54,6 +66,9 @@ const APP: () = {
         button1: button::Button<Button1Pin>,
         button2: button::Button<Button2Pin>,
         button3: button::Button<Button3Pin>,
+        radio: radio::Receiver<RadioPin>,
+        gps_rx: serial::Rx<stm32::USART3>,
+        nmea: nmea::Parser,
         display: EPaperDisplay,
         spi: Spi,
         ui: ui::Model,
@@ -61,6 +76,9 @@ const APP: () = {
         full_update: bool,
         timer: timer::CountDownTimer<stm32::TIM3>,
         backup_domain: stm32f1xx_hal::backup_domain::BackupDomain,
+        calibration: calibration::Calibration,
+        storage: storage::Storage,
+        alarm_storage: storage::Storage,
     }
 
     #[init(spawn = [msg])]
@@ -91,6 +109,7 @@ const APP: () = {
         let button1_pin = gpioa.pa7.into_pull_up_input(&mut gpioa.crl);
         let button2_pin = gpiob.pb0.into_pull_up_input(&mut gpiob.crl);
         let button3_pin = gpiob.pb1.into_pull_up_input(&mut gpiob.crl);
+        let radio_pin = gpiob.pb8.into_floating_input(&mut gpiob.crh);
 
         let mut timer =
             timer::Timer::tim3(c.device.TIM3, &clocks, &mut rcc.apb1).start_count_down(1.khz());
@@ -102,28 +121,54 @@ const APP: () = {
         let mut rtc_dev = rtc::Rtc::rtc(c.device.RTC, &mut backup_domain);
         if rtc_dev.current_time() < 100 {
             let today = DateTime {
-                year: 2020,
-                month: 1,
-                day: 1,
-                hour: 0,
-                min: 0,
-                sec: 0,
-                day_of_week: datetime::DayOfWeek::Wednesday,
+                date: Date::new(2020, 1, 1).unwrap(),
+                time: Time::MIDNIGHT,
             };
             if let Some(epoch) = today.to_epoch() {
                 rtc_dev.set_time(epoch);
             }
         }
         rtc_dev.listen_seconds();
+        let tz = TimeZone::parse(TIME_ZONE).expect("invalid time zone");
+
+        let storage = unsafe { storage::Storage::new(storage::SETTINGS) };
+        let settings = Settings::try_from(storage.read()).unwrap_or_default();
+        let mut alarm_storage = unsafe { storage::Storage::new(storage::ALARMS) };
 
         let mut alarm_manager = alarm::AlarmManager::default();
-        for i in 0..5 {
-            let d0 = backup_domain.read_data_register_low(i * 2);
-            let d1 = backup_domain.read_data_register_low(i * 2 + 1);
-            if let Some(alarm) = alarm::Alarm::try_from(d0 as u32 | (d1 as u32) << 16) {
-                alarm_manager.alarms[i] = alarm;
+        alarm_manager.holidays = settings.holidays;
+        alarm_manager.grace_mins = settings.grace_mins;
+        let backup = alarm_backup::read(&backup_domain);
+        match alarm::AlarmManager::alarms_try_from(alarm_storage.read()) {
+            Some(alarms) => alarm_manager.alarms = alarms,
+            // saved in the backup registers by an older firmware, moved
+            // to the flash once, the registers being reset below
+            None if backup.is_none() => {
+                for i in 0..5 {
+                    let d0 = backup_domain.read_data_register_low(i * 2);
+                    let d1 = backup_domain.read_data_register_low(i * 2 + 1);
+                    if let Some(alarm) = alarm::Alarm::try_from(d0 as u32 | (d1 as u32) << 16) {
+                        let _ = alarm_manager.add(alarm);
+                    }
+                }
+                if alarm_storage.write(&alarm_manager.as_u16s()).is_err() {
+                    c.spawn.msg(ui::Msg::FailStorage).unwrap();
+                }
             }
+            None => {}
         }
+        if backup.is_none() {
+            alarm_backup::reset(&mut backup_domain);
+        }
+        alarm_manager.set_backup(backup.unwrap_or([0; 2]));
+
+        // after the alarms of an older firmware, in the same registers
+        let calibration = rtc_calibration::read(&backup_domain).unwrap_or_else(|| {
+            let calibration = calibration::Calibration::new(calibration::NEUTRAL);
+            rtc_calibration::write(&mut backup_domain, &calibration);
+            calibration
+        });
+        rtc_calibration::set_prescaler(&mut rtc_dev);
 
         let mut delay = delay::Delay::new(c.core.SYST, clocks);
 
@@ -169,9 +214,19 @@ const APP: () = {
         let mut bme280 = bme280::BME280::new_primary(i2c, delay);
         bme280.init().expect("i2c init error");
 
-        c.spawn
-            .msg(ui::Msg::AlarmManager(alarm_manager.clone()))
-            .unwrap();
+        // only the TX pin of the GPS module is connected, to PB11
+        let tx = gpiob.pb10.into_alternate_push_pull(&mut gpiob.crh);
+        let rx = gpiob.pb11;
+        let gps_serial = serial::Serial::usart3(
+            c.device.USART3,
+            (tx, rx),
+            &mut afio.mapr,
+            serial::Config::default().baudrate(9600.bps()),
+            clocks,
+            &mut rcc.apb1,
+        );
+        let (_, mut gps_rx) = gps_serial.split();
+        gps_rx.listen();
 
         init::LateResources {
             rtc_dev,
@@ -181,21 +236,27 @@ const APP: () = {
             button1: button::Button::new(button1_pin),
             button2: button::Button::new(button2_pin),
             button3: button::Button::new(button3_pin),
+            radio: radio::Receiver::new(radio_pin, RADIO_PROTOCOL),
+            gps_rx,
+            nmea: nmea::Parser::default(),
             display: il3820,
             spi,
-            ui: ui::Model::init(),
+            ui: ui::Model::init(tz.clone(), settings),
+            tz,
             alarm_manager,
             timer,
             backup_domain,
+            calibration,
+            storage,
+            alarm_storage,
         }
     }
 
-    #[task(binds = TIM3, priority = 4, spawn = [msg], resources = [button0, button1, button2, button3, sound, timer])]
+    #[task(binds = TIM3, priority = 4, spawn = [msg], resources = [button0, button1, button2, button3, radio, sound, timer])]
     fn tick(c: tick::Context) {
         c.resources.timer.clear_update_interrupt_flag();
 
         if let button::Event::Pressed = c.resources.button0.poll() {
-            c.resources.sound.stop();
             c.spawn.msg(ui::Msg::ButtonCancel).unwrap();
         }
         if let button::Event::Pressed = c.resources.button1.poll() {
@@ -207,20 +268,51 @@ const APP: () = {
         if let button::Event::Pressed = c.resources.button3.poll() {
             c.spawn.msg(ui::Msg::ButtonOk).unwrap();
         }
-        c.resources.sound.poll();
+        if let Some(decoded) = c.resources.radio.poll() {
+            c.spawn.msg(ui::Msg::RadioTime(decoded)).unwrap();
+        }
+        if c.resources.sound.poll() {
+            c.spawn.msg(ui::Msg::SoundEnded).unwrap();
+        }
+    }
+
+    #[task(binds = USART3, priority = 3, spawn = [msg], resources = [gps_rx, nmea])]
+    fn gps(c: gps::Context) {
+        if let Ok(byte) = c.resources.gps_rx.read() {
+            if let Some(time) = c.resources.nmea.push(byte) {
+                c.spawn.msg(ui::Msg::GpsTime(time)).unwrap();
+            }
+        }
     }
 
-    #[task(binds = RTC, priority = 3, spawn = [msg], resources = [rtc_dev, bme280, alarm_manager, sound])]
+    #[task(binds = RTC, priority = 3, spawn = [msg], resources = [rtc_dev, &tz, bme280, alarm_manager, backup_domain, sound])]
     fn rtc_task(mut c: rtc_task::Context) {
+        // counting the interrupts, the RTC counter may be set
+        static mut UPTIME: u32 = 0;
         c.resources.rtc_dev.clear_second_flag();
+        *UPTIME = UPTIME.wrapping_add(1);
+        c.spawn.msg(ui::Msg::Uptime(*UPTIME)).unwrap();
 
-        let datetime = DateTime::new(c.resources.rtc_dev.current_time());
-        if datetime.sec == 0 && c.resources.alarm_manager.must_ring(&datetime) {
+        let utc = i64::from(c.resources.rtc_dev.current_time());
+        // always in the years of `DateTime` from the 32 bit counter
+        let datetime = c.resources.tz.to_local(utc).unwrap();
+        // every second, the alarms skipped by a lost interrupt or a
+        // setting of the clock being caught up
+        let backup = c.resources.alarm_manager.backup_as_u16s();
+        let ids = c
+            .resources
+            .alarm_manager
+            .must_ring(utc, c.resources.tz, *UPTIME);
+        // a skipped ring
+        let new_backup = c.resources.alarm_manager.backup_as_u16s();
+        if new_backup != backup {
+            alarm_backup::write(c.resources.backup_domain, new_backup);
+        }
+        if let Some(&id) = ids.first() {
             c.resources
                 .sound
                 .lock(|alarm| alarm.play(&SO_WHAT, 10 * 60));
-            let manager = c.resources.alarm_manager.clone();
-            c.spawn.msg(ui::Msg::AlarmManager(manager)).unwrap();
+            c.spawn.msg(ui::Msg::AlarmRinging(id)).unwrap();
         }
         c.spawn.msg(ui::Msg::DateTime(datetime)).unwrap();
 
@@ -236,43 +328,104 @@ const APP: () = {
         c.spawn.msg(msg).unwrap();
     }
 
-    #[task(priority = 2, capacity = 16, spawn = [msg], resources = [ui, rtc_dev, full_update, alarm_manager, backup_domain])]
+    #[task(priority = 2, capacity = 16, spawn = [msg], resources = [ui, rtc_dev, &tz, full_update, alarm_manager, backup_domain, calibration, sound, storage, alarm_storage])]
     fn msg(mut c: msg::Context, msg: ui::Msg) {
         use crate::ui::Cmd::*;
-        for cmd in c.resources.ui.update(msg) {
+        // the manager not copied in the messages, as too big for the queue
+        let ui = &mut c.resources.ui;
+        let cmds = c.resources.alarm_manager.lock(|m| ui.update(msg, m));
+        for cmd in cmds {
             match cmd {
-                UpdateRtc(dt) => {
+                UpdateRtc(dt, precise) => {
                     if let Some(epoch) = dt.to_epoch() {
-                        c.resources.rtc_dev.lock(|rtc| {
+                        let rtc_time = c.resources.rtc_dev.lock(|rtc| {
+                            let rtc_time = rtc.current_time();
                             let _ = rtc.set_time(epoch);
+                            rtc_time
                         });
-                        c.spawn.msg(ui::Msg::DateTime(dt)).unwrap();
+                        // the reference of the measurement changed too
+                        let calibration = &mut c.resources.calibration;
+                        if precise {
+                            calibration.rtc_set(rtc_time, epoch);
+                        } else {
+                            calibration.rtc_set_manually();
+                        }
+                        c.resources
+                            .backup_domain
+                            .lock(|bkp| rtc_calibration::write(bkp, calibration));
+                        if let Some(local) = c.resources.tz.to_local(i64::from(epoch)) {
+                            c.spawn.msg(ui::Msg::DateTime(local)).unwrap();
+                        }
                     }
                 }
                 UpdateAlarm(alarm, i) => {
-                    let data = alarm.as_u32();
-                    c.resources
-                        .backup_domain
-                        .write_data_register_low(i * 2, data as u16);
-                    c.resources
-                        .backup_domain
-                        .write_data_register_low(i * 2 + 1, (data >> 16) as u16);
-                    let manager = c.resources.alarm_manager.lock(|m| {
+                    let data = c.resources.alarm_manager.lock(|m| {
                         m.alarms[i] = alarm;
-                        m.clone()
+                        m.as_u16s()
+                    });
+                    if c.resources.alarm_storage.write(&data).is_err() {
+                        c.spawn.msg(ui::Msg::FailStorage).unwrap();
+                    }
+                }
+                AddAlarm(alarm) => {
+                    let data = c.resources.alarm_manager.lock(|m| {
+                        let _ = m.add(alarm);
+                        m.as_u16s()
+                    });
+                    if c.resources.alarm_storage.write(&data).is_err() {
+                        c.spawn.msg(ui::Msg::FailStorage).unwrap();
+                    }
+                }
+                RemoveAlarm(i) => {
+                    let backup_domain = &mut c.resources.backup_domain;
+                    let data = c.resources.alarm_manager.lock(|m| {
+                        m.remove(i);
+                        backup_domain.lock(|bkp| alarm_backup::write(bkp, m.backup_as_u16s()));
+                        m.as_u16s()
+                    });
+                    if c.resources.alarm_storage.write(&data).is_err() {
+                        c.spawn.msg(ui::Msg::FailStorage).unwrap();
+                    }
+                }
+                SkipAlarm(i, skipped) => {
+                    let backup_domain = &mut c.resources.backup_domain;
+                    c.resources.alarm_manager.lock(|m| {
+                        m.set_skipped(i, skipped);
+                        backup_domain.lock(|bkp| alarm_backup::write(bkp, m.backup_as_u16s()));
+                    });
+                }
+                SetVacation(until) => {
+                    let backup_domain = &mut c.resources.backup_domain;
+                    c.resources.alarm_manager.lock(|m| {
+                        m.vacation_until = until;
+                        backup_domain.lock(|bkp| alarm_backup::write(bkp, m.backup_as_u16s()));
+                    });
+                }
+                UpdateSettings(settings) => {
+                    if c.resources.storage.write(&settings.as_u16s()).is_err() {
+                        c.spawn.msg(ui::Msg::FailStorage).unwrap();
+                    }
+                    c.resources.alarm_manager.lock(|m| {
+                        m.holidays = settings.holidays;
+                        m.grace_mins = settings.grace_mins;
                     });
-                    c.spawn.msg(ui::Msg::AlarmManager(manager)).unwrap();
                 }
+                SnoozeAlarm(uptime) => c.resources.alarm_manager.lock(|m| m.snooze(uptime)),
+                DismissAlarm => c.resources.alarm_manager.lock(|m| m.dismiss()),
+                ClearMissed => c.resources.alarm_manager.lock(|m| m.missed = None),
+                StopSound => c.resources.sound.lock(|sound| sound.stop()),
                 FullUpdate => *c.resources.full_update = true,
             }
         }
         rtfm::pend(stm32::Interrupt::EXTI1);
     }
 
-    #[task(binds = EXTI1, priority = 1, resources = [ui, display, spi, full_update])]
+    #[task(binds = EXTI1, priority = 1, resources = [ui, display, spi, full_update, alarm_manager])]
     fn render(mut c: render::Context) {
         let model = c.resources.ui.lock(|model| model.clone());
-        let display = model.view();
+        // not locked while drawing, that would delay the GPS and the RTC
+        let alarm_manager = c.resources.alarm_manager.lock(|m| m.clone());
+        let display = model.view(&alarm_manager);
         let full_update = c
             .resources
             .full_update
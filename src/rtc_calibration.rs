@@ -0,0 +1,57 @@
+//! Registers of the RTC calibration, see `portable::calibration`. The
+//! calibration value and the reference of the drift measurement are
+//! kept in the backup domain, the prescaler is set at each reset by the
+//! HAL.
+//!
+//! The calibration is valid once marked in the data register after the
+//! ones of `alarm_backup`, the reference being in the next ones: it is 0
+//! after a reset of the backup domain, and an older firmware did not use
+//! it.
+
+use portable::calibration::Calibration;
+use stm32f1xx_hal::backup_domain::BackupDomain;
+use stm32f1xx_hal::{rtc, stm32};
+
+/// The prescaler of the HAL minus two pulses: the RTC counts a second
+/// every 32766 pulses, before the calibration.
+const PRESCALER: u32 = 32_765;
+const MARKER_REGISTER: usize = 3;
+const MARKER: u16 = 0xca1b;
+
+/// Shortens the prescaler, to call after `Rtc::rtc` once the calibration
+/// value is valid, as the RTC is 61ppm fast with a calibration value of 0.
+pub fn set_prescaler(_rtc: &mut rtc::Rtc) {
+    let regs = unsafe { &*stm32::RTC::ptr() };
+    let wait = || while regs.crl.read().rtoff().bit_is_clear() {};
+    wait();
+    regs.crl.modify(|_, w| w.cnf().set_bit());
+    regs.prlh.write(|w| unsafe { w.bits(PRESCALER >> 16) });
+    regs.prll.write(|w| unsafe { w.bits(PRESCALER & 0xffff) });
+    regs.crl.modify(|_, w| w.cnf().clear_bit());
+    wait();
+}
+
+/// `None` if the calibration is not valid.
+pub fn read(bkp: &BackupDomain) -> Option<Calibration> {
+    if bkp.read_data_register_low(MARKER_REGISTER) != MARKER {
+        return None;
+    }
+    let regs = unsafe { &*stm32::BKP::ptr() };
+    let mut calibration = Calibration::new(regs.rtccr.read().cal().bits());
+    let mut data = [0; 4];
+    for (i, half_word) in data.iter_mut().enumerate() {
+        *half_word = bkp.read_data_register_low(MARKER_REGISTER + 1 + i);
+    }
+    calibration.set_backup(data);
+    Some(calibration)
+}
+
+pub fn write(bkp: &mut BackupDomain, calibration: &Calibration) {
+    let regs = unsafe { &*stm32::BKP::ptr() };
+    regs.rtccr
+        .modify(|_, w| unsafe { w.cal().bits(calibration.value) });
+    for (i, &half_word) in calibration.backup_as_u16s().iter().enumerate() {
+        bkp.write_data_register_low(MARKER_REGISTER + 1 + i, half_word);
+    }
+    bkp.write_data_register_low(MARKER_REGISTER, MARKER);
+}
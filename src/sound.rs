@@ -59,9 +59,10 @@ impl Sound {
         self.speaker.rest();
         self.speaker.mute();
     }
-    pub fn poll(&mut self) {
+    /// Returns true when the song ends by itself, not stopped.
+    pub fn poll(&mut self) -> bool {
         if !self.playing {
-            return;
+            return false;
         }
 
         use pwm_speaker::songs::MsEvent::*;
@@ -69,7 +70,11 @@ impl Sound {
             Some(BeginNote { pitch }) => self.speaker.play(pitch),
             Some(EndNote) => self.speaker.rest(),
             Some(Wait) => (),
-            None => self.stop(),
+            None => {
+                self.stop();
+                return true;
+            }
         }
+        false
     }
 }
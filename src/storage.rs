@@ -0,0 +1,87 @@
+//! Persistent storage in the last pages of the flash, reserved in
+//! `memory.x`.
+
+use stm32f1xx_hal::stm32;
+
+/// The page of the settings.
+pub const SETTINGS: u32 = 0x0800_fc00;
+/// The page of the alarms.
+pub const ALARMS: u32 = 0x0800_f800;
+const PAGE_SIZE: usize = 1024;
+const KEY1: u32 = 0x4567_0123;
+const KEY2: u32 = 0xcdef_89ab;
+
+/// A failure of the flash, the page being left partly written.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Error {
+    /// A half word not erased before being written.
+    Programming,
+    WriteProtected,
+    /// The operation did not signal its end.
+    NotCompleted,
+}
+
+pub struct Storage {
+    page: u32,
+}
+impl Storage {
+    /// There must be only one `Storage` by page, all used by the same
+    /// task. The HAL only uses the `ACR` register of the flash, the
+    /// other ones are used here.
+    pub unsafe fn new(page: u32) -> Self {
+        Storage { page }
+    }
+    /// The content of the page, `0xffff` when erased.
+    pub fn read(&self) -> &[u16] {
+        unsafe { core::slice::from_raw_parts(self.page as *const u16, PAGE_SIZE / 2) }
+    }
+    /// Erases the page and writes `data` at its beginning. The CPU
+    /// stalls during the erase, about 20ms.
+    pub fn write(&mut self, data: &[u16]) -> Result<(), Error> {
+        let flash = unsafe { &*stm32::FLASH::ptr() };
+
+        flash.keyr.write(|w| unsafe { w.key().bits(KEY1) });
+        flash.keyr.write(|w| unsafe { w.key().bits(KEY2) });
+        let result = self.erase_and_program(flash, data);
+        flash
+            .cr
+            .modify(|_, w| w.per().clear_bit().pg().clear_bit().lock().set_bit());
+        result
+    }
+    fn erase_and_program(
+        &self,
+        flash: &stm32::flash::RegisterBlock,
+        data: &[u16],
+    ) -> Result<(), Error> {
+        flash.cr.modify(|_, w| w.per().set_bit());
+        flash.ar.write(|w| unsafe { w.far().bits(self.page) });
+        flash.cr.modify(|_, w| w.strt().set_bit());
+        end_of_operation(flash)?;
+        flash.cr.modify(|_, w| w.per().clear_bit());
+
+        flash.cr.modify(|_, w| w.pg().set_bit());
+        for (i, &half_word) in data.iter().take(PAGE_SIZE / 2).enumerate() {
+            unsafe { core::ptr::write_volatile((self.page as *mut u16).add(i), half_word) };
+            end_of_operation(flash)?;
+        }
+        Ok(())
+    }
+}
+
+/// Waits for the end of the operation in progress, and clears its flags.
+fn end_of_operation(flash: &stm32::flash::RegisterBlock) -> Result<(), Error> {
+    while flash.sr.read().bsy().bit_is_set() {}
+    let sr = flash.sr.read();
+    flash
+        .sr
+        .write(|w| w.eop().set_bit().pgerr().set_bit().wrprterr().set_bit());
+    if sr.wrprterr().bit_is_set() {
+        Err(Error::WriteProtected)
+    } else if sr.pgerr().bit_is_set() {
+        Err(Error::Programming)
+    } else if sr.eop().bit_is_clear() {
+        Err(Error::NotCompleted)
+    } else {
+        Ok(())
+    }
+}